@@ -1,45 +1,768 @@
+use std::env;
+use std::fs;
 use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::Duration;
 
-use clock::stopwatch::{format_time, Stopwatch};
+use chrono::{DateTime, Local, NaiveTime};
+use clock::comparison::ReferenceRun;
+use clock::deadline::DeadlineTimer;
+use clock::driver::{CancellationToken, TimerDriver};
+use clock::export::Session;
+use clock::pomodoro::{InterruptionKind, Pomodoro};
+use clock::race::{Bib, Race};
+use clock::sequence::{Phase, Sequence};
+use clock::snapshot::{StopwatchSnapshot, TimerSetSnapshot, TimerSnapshot};
+use clock::stopwatch::{format_delta, format_signed_time, format_time, Stopwatch};
+use clock::timer::{CompletionPolicy, Timer, TimerState};
+use clock::timer_set::{TimerId, TimerSet};
+use serde::{Deserialize, Serialize};
+
+/// The stopwatch and timers saved after every command and restored when the CLI starts again.
+#[derive(Default, Serialize, Deserialize)]
+struct SavedState {
+    stopwatch: Option<StopwatchSnapshot>,
+    timer: Option<TimerSnapshot>,
+    #[serde(default)]
+    timer_set: Option<TimerSetSnapshot>,
+    /// The timers as saved by earlier versions, without their ids.
+    #[serde(default, skip_serializing)]
+    timers: Vec<(String, TimerSnapshot)>,
+}
 
 fn main() {
-    let mut stopwatch = Stopwatch::new();
+    let saved = load_state();
+    let mut stopwatch = saved
+        .stopwatch
+        .map_or_else(Stopwatch::new, Stopwatch::restore);
+    let mut timer: Option<TimerDriver> = saved
+        .timer
+        .map(|snapshot| TimerDriver::new(Timer::restore(snapshot)));
+    let mut timers = saved
+        .timer_set
+        .map_or_else(TimerSet::new, TimerSet::restore);
+    for (label, snapshot) in saved.timers {
+        timers.insert(label, Timer::restore(snapshot));
+    }
+    let mut intervals: Option<Sequence> = None;
+    let mut pomodoro = Pomodoro::default();
+    let mut deadline: Option<DeadlineTimer> = None;
+    let mut reference: Option<ReferenceRun> = None;
+    let mut race = Race::new();
 
     loop {
         let mut input = String::new();
 
-        print!("Enter a command (start, stop, lap, quit): ");
+        print!("Enter a command (help, quit): ");
         io::stdout().flush().unwrap();
-        io::stdin().read_line(&mut input).unwrap();
-
-        match input.trim() {
-            "start" => {
-                stopwatch.start();
-                println!("Stopwatch started!");
-            }
-            "stop" => {
-                stopwatch.stop();
-                println!("Stopwatch stopped!");
-            }
-            "lap" => {
-                stopwatch.add_lap();
-                let lap_times = stopwatch.lap_times();
-
-                // Print table header
-                println!("{:<10}  {:<15}  {:<15}", "Lap", "Split Time", "Total Time");
-
-                // Print table rows
-                for lap in lap_times {
-                    println!(
-                        "{:<10}  {:<15}  {:<15}",
-                        lap.lap_number(),
-                        format_time(lap.split_time()),
-                        format_time(lap.total_time())
-                    );
+        if io::stdin().read_line(&mut input).unwrap() == 0 {
+            break;
+        }
+
+        let mut words = input.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("start"), None) => match stopwatch.try_start() {
+                Ok(()) => println!("Stopwatch started!"),
+                Err(err) => println!("Could not start the stopwatch: {err}."),
+            },
+            (Some("start"), Some(secs)) => {
+                match secs.parse::<f64>().map(Duration::try_from_secs_f64) {
+                    Ok(Ok(countdown)) => match stopwatch.try_start_after(countdown) {
+                        Ok(()) => println!("Stopwatch starts in {}.", format_time(countdown)),
+                        Err(err) => println!("Could not start the stopwatch: {err}."),
+                    },
+                    _ => println!("Use `start <secs>` to start the stopwatch after a countdown."),
+                }
+            }
+            (Some("cancel"), None) => {
+                if stopwatch.cancel_countdown() {
+                    println!("Countdown cancelled.");
+                } else {
+                    println!("The stopwatch is not counting down.");
+                }
+            }
+            (Some("stop"), None) => match stopwatch.try_stop() {
+                Ok(()) => println!("Stopwatch stopped!"),
+                Err(err) => println!("Could not stop the stopwatch: {err}."),
+            },
+            (Some("segments"), None) => print_segments(&stopwatch),
+            (Some("export"), Some(format)) => export_command(&stopwatch, format, words.next()),
+            (Some("import"), Some(path)) => {
+                if let Some(session) = import_session(path) {
+                    stopwatch = Stopwatch::restore(session.to_snapshot());
+                    print_laps(&stopwatch, reference.as_ref());
                 }
             }
-            "quit" => break,
+            (Some("lap"), None) => {
+                if let Err(err) = stopwatch.try_add_lap() {
+                    println!("Could not add a lap: {err}.");
+                    continue;
+                }
+                print_laps(&stopwatch, reference.as_ref());
+            }
+            (Some("laps"), Some(command)) => {
+                laps_command(&mut stopwatch, &mut reference, command, words);
+            }
+            (Some("timer"), Some(command)) => timer_command(&mut timer, command, words.next()),
+            (Some("timers"), Some(command)) => timers_command(&mut timers, command, words),
+            (Some("intervals"), Some(command)) => {
+                intervals_command(&mut intervals, command, words);
+            }
+            (Some("pomodoro"), Some(command)) => pomodoro_command(&mut pomodoro, command),
+            (Some("deadline"), Some(command)) => deadline_command(&mut deadline, command),
+            (Some("race"), Some(command)) => race_command(&mut race, command, words),
+            (Some("help"), None) => print_help(),
+            (Some("quit"), None) => break,
             _ => println!("Invalid command."),
         }
+
+        // Saving after every command keeps the state when the CLI is interrupted or killed.
+        save_state(&SavedState::new(&stopwatch, timer.as_ref(), &timers));
+    }
+
+    save_state(&SavedState::new(&stopwatch, timer.as_ref(), &timers));
+}
+
+impl SavedState {
+    /// Takes snapshots of the stopwatch and timers.
+    fn new(stopwatch: &Stopwatch, timer: Option<&TimerDriver>, timers: &TimerSet) -> Self {
+        Self {
+            stopwatch: Some(stopwatch.snapshot()),
+            timer: timer.map(|timer| timer.timer().snapshot()),
+            timer_set: Some(timers.snapshot()),
+            timers: Vec::new(),
+        }
+    }
+}
+
+/// The commands the CLI understands, by group.
+const COMMANDS: [(&str, &str); 10] = [
+    ("Stopwatch", "start [<secs>], cancel, stop, lap, segments"),
+    (
+        "Laps",
+        "laps <list|reference|label <n> <text>|note <n> <text>|remove <n>|insert <secs>>",
+    ),
+    ("Sessions", "export <csv|json|md|html> [<path>], import <path>"),
+    (
+        "Timer",
+        "timer <secs|start|stop|reset|overtime|autoreset|repeat <n|forever>|wait|status>",
+    ),
+    (
+        "Timers",
+        "timers <add <secs> <label>|start|stop|reset|remove <id>|pause|resume|list>",
+    ),
+    (
+        "Intervals",
+        "intervals <<warmup> <work> <rest> <rounds> <cooldown>|start|stop|reset|skip|repeat|status>",
+    ),
+    ("Pomodoro", "pomodoro <start|stop|reset|skip|interrupt|status>"),
+    ("Deadline", "deadline <HH:MM|status>"),
+    (
+        "Race",
+        "race <add <bib> <name>|start [<secs>]|interval <secs>|split <bib>|finish <bib>|correct <record> <bib>|remove <record>|records|results>",
+    ),
+    ("Other", "help, quit"),
+];
+
+/// Prints the commands the CLI understands, one line per group.
+fn print_help() {
+    for (group, usage) in COMMANDS {
+        println!("{:<10}  {usage}", format!("{group}:"));
+    }
+}
+
+/// Returns where the state is saved: `$CLOCK_STATE`, or `.clock-state.json` in the home directory.
+fn state_path() -> Option<PathBuf> {
+    env::var_os("CLOCK_STATE")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".clock-state.json")))
+}
+
+/// Loads the state saved when the CLI last ran, or an empty state if there is none.
+fn load_state() -> SavedState {
+    let Some(contents) = state_path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return SavedState::default();
+    };
+    serde_json::from_str(&contents).unwrap_or_else(|err| {
+        println!("Could not restore the saved state: {err}.");
+        SavedState::default()
+    })
+}
+
+/// Saves the state so that it can be restored when the CLI starts again.
+fn save_state(state: &SavedState) {
+    let Some(path) = state_path() else {
+        return;
+    };
+    let result = serde_json::to_string(state)
+        .map_err(io::Error::from)
+        .and_then(|contents| fs::write(path, contents));
+    if let Err(err) = result {
+        println!("Could not save the state: {err}.");
+    }
+}
+
+/// Runs a `timer` sub-command, which either creates a timer from a number of seconds or acts on
+/// the current one.
+fn timer_command(timer: &mut Option<TimerDriver>, command: &str, argument: Option<&str>) {
+    if let Ok(secs) = command.parse() {
+        *timer = Some(TimerDriver::new(Timer::new(Duration::from_secs(secs))));
+        println!("Timer set for {secs} seconds.");
+        return;
+    }
+
+    let Some(timer) = timer else {
+        println!("No timer set. Use `timer <secs>` first.");
+        return;
+    };
+
+    let result = match command {
+        "start" => timer.update(Timer::try_start),
+        "stop" => timer.update(Timer::try_stop),
+        "reset" => {
+            timer.update(Timer::reset);
+            Ok(())
+        }
+        "overtime" => {
+            timer.update(|timer| timer.set_overtime(!timer.is_overtime_enabled()));
+            Ok(())
+        }
+        "autoreset" => {
+            timer.update(|timer| timer.set_completion_policy(CompletionPolicy::AutoReset));
+            Ok(())
+        }
+        "repeat" => {
+            let policy = match argument.map(|repeats| (repeats, repeats.parse())) {
+                Some(("forever", _)) => CompletionPolicy::RepeatForever,
+                Some((_, Ok(repeats))) => CompletionPolicy::Repeat(repeats),
+                _ => {
+                    println!("Use `timer repeat <n>` to repeat the timer n more times, or `timer repeat forever`.");
+                    return;
+                }
+            };
+            timer.update(|timer| timer.set_completion_policy(policy));
+            Ok(())
+        }
+        "wait" => {
+            // The status printed below tells whether the timer expired or was not running.
+            let _ = timer.wait(&CancellationToken::new());
+            Ok(())
+        }
+        "status" => Ok(()),
+        _ => {
+            println!("Invalid command.");
+            return;
+        }
+    };
+
+    match result {
+        Ok(()) => print_timer_status(&timer.timer()),
+        Err(err) => println!("Could not {command} the timer: {err}."),
+    }
+}
+
+/// Prints the times the stopwatch ran and how long it was paused in between.
+fn print_segments(stopwatch: &Stopwatch) {
+    let local_time = |time| DateTime::<Local>::from(time).format("%H:%M:%S").to_string();
+    for segment in stopwatch.segments() {
+        println!(
+            "{:<10}  {:<10}  {}",
+            local_time(segment.started_at()),
+            segment.stopped_at().map(local_time).unwrap_or_default(),
+            format_time(segment.duration())
+        );
+    }
+    println!(
+        "Ran {}, paused {}.",
+        format_time(stopwatch.elapsed_time()),
+        format_time(stopwatch.paused_time())
+    );
+}
+
+/// Runs an `export` command, which saves the stopwatch session to a file in the given format.
+fn export_command(stopwatch: &Stopwatch, format: &str, path: Option<&str>) {
+    let session = Session::from_stopwatch(stopwatch);
+    let contents = match format {
+        "csv" => session.to_csv(),
+        "md" => session.to_markdown(),
+        "html" => session.to_html(),
+        "json" => match session.to_json() {
+            Ok(json) => json,
+            Err(err) => {
+                println!("Could not export the session: {err}.");
+                return;
+            }
+        },
+        _ => {
+            println!("Use `export <csv|json|md|html> <path>` to export the session.");
+            return;
+        }
+    };
+    let Some(path) = path else {
+        print!("{contents}");
+        return;
+    };
+    match fs::write(path, contents) {
+        Ok(()) => println!("Session exported to {path}."),
+        Err(err) => println!("Could not export the session: {err}."),
+    }
+}
+
+/// Reads a session exported with `export json`.
+fn import_session(path: &str) -> Option<Session> {
+    let result = fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|json| Session::from_json(&json).map_err(|err| err.to_string()));
+    match result {
+        Ok(session) => Some(session),
+        Err(err) => {
+            println!("Could not import the session: {err}.");
+            None
+        }
+    }
+}
+
+/// Prints the laps of the stopwatch and statistics over them.
+///
+/// With a reference run, each lap is also compared against the reference lap with the same number.
+fn print_laps(stopwatch: &Stopwatch, reference: Option<&ReferenceRun>) {
+    let lap_times = stopwatch.lap_times();
+    let comparisons = reference.map(|reference| reference.compare(lap_times));
+
+    // Print table header
+    println!(
+        "{:<10}  {:<15}  {:<15}  {:<10}  {:<10}  {:<10}  {:<15}  Note",
+        "Lap", "Split Time", "Total Time", "Delta", "Cumulative", "Time", "Label"
+    );
+
+    // Print table rows
+    for (index, lap) in lap_times.iter().enumerate() {
+        let (delta, cumulative) = comparisons
+            .as_ref()
+            .and_then(|comparisons| comparisons.get(index))
+            .map(|comparison| {
+                (
+                    format_delta(comparison.split_delta()),
+                    format_delta(comparison.cumulative_delta()),
+                )
+            })
+            .unwrap_or_default();
+        println!(
+            "{:<10}  {:<15}  {:<15}  {:<10}  {:<10}  {:<10}  {:<15}  {}",
+            lap.lap_number(),
+            format_time(lap.split_time()),
+            format_time(lap.total_time()),
+            delta,
+            cumulative,
+            lap.recorded_at()
+                .map(|time| time.format("%H:%M:%S").to_string())
+                .unwrap_or_default(),
+            lap.label().unwrap_or_default(),
+            lap.note().unwrap_or_default()
+        );
+    }
+
+    if let Some(finish) = reference
+        .and_then(|reference| reference.projected_finish(lap_times, stopwatch.elapsed_time()))
+    {
+        println!("Projected finish {}.", format_time(finish));
+    }
+
+    let statistics = stopwatch.lap_statistics();
+    if let (Some(fastest), Some(slowest), Some(mean), Some(deviation)) = (
+        statistics.fastest(),
+        statistics.slowest(),
+        statistics.mean(),
+        statistics.standard_deviation(),
+    ) {
+        println!(
+            "Fastest lap {}, slowest lap {}, mean {} (± {}).",
+            fastest.lap_number(),
+            slowest.lap_number(),
+            format_time(mean),
+            format_time(deviation)
+        );
+    }
+}
+
+/// Runs a `laps` sub-command, which edits the laps of the stopwatch.
+fn laps_command<'a>(
+    stopwatch: &mut Stopwatch,
+    reference: &mut Option<ReferenceRun>,
+    command: &str,
+    mut arguments: impl Iterator<Item = &'a str>,
+) {
+    match command {
+        "list" => {}
+        "reference" => {
+            *reference = Some(ReferenceRun::from_laps(stopwatch.lap_times()));
+            println!("The current laps are now the reference run.");
+        }
+        "insert" => {
+            let Some(Ok(secs)) = arguments.next().map(str::parse::<f64>) else {
+                println!("Use `laps insert <secs>` to insert a lap.");
+                return;
+            };
+            let Ok(total_time) = Duration::try_from_secs_f64(secs) else {
+                println!("Invalid lap time.");
+                return;
+            };
+            if let Err(err) = stopwatch.insert_lap(total_time) {
+                println!("Could not insert the lap: {err}.");
+                return;
+            }
+        }
+        "label" | "note" | "remove" => {
+            let Some(Ok(lap_number)) = arguments.next().map(str::parse) else {
+                println!("Use `laps {command} <n>` to {command} a lap.");
+                return;
+            };
+            let text =
+                Some(arguments.collect::<Vec<_>>().join(" ")).filter(|text| !text.is_empty());
+            let found = match command {
+                "remove" => stopwatch.remove_lap(lap_number).is_some(),
+                _ => stopwatch
+                    .lap_mut(lap_number)
+                    .map(|lap| match command {
+                        "label" => lap.set_label(text),
+                        _ => lap.set_note(text),
+                    })
+                    .is_some(),
+            };
+            if !found {
+                println!("No lap {lap_number}.");
+                return;
+            }
+        }
+        _ => {
+            println!("Invalid command.");
+            return;
+        }
+    }
+
+    print_laps(stopwatch, reference.as_ref());
+}
+
+/// Runs a `timers` sub-command, which acts on the set of labelled timers.
+fn timers_command<'a>(
+    timers: &mut TimerSet,
+    command: &str,
+    mut arguments: impl Iterator<Item = &'a str>,
+) {
+    match command {
+        "add" => {
+            let Some(Ok(secs)) = arguments.next().map(str::parse) else {
+                println!("Use `timers add <secs> <label>` to add a timer.");
+                return;
+            };
+            let label = arguments.collect::<Vec<_>>().join(" ");
+            let id = timers.add(label, Duration::from_secs(secs));
+            println!("Timer {id} added.");
+        }
+        "pause" => timers.pause_all(),
+        "resume" => timers.resume_all(),
+        "list" => {}
+        "start" | "stop" | "reset" | "remove" => {
+            let Some(id) = arguments.next().and_then(|id| id.parse::<TimerId>().ok()) else {
+                println!("Use `timers {command} <id>` to {command} a timer.");
+                return;
+            };
+            let found = match command {
+                "remove" => timers.remove(id).is_some(),
+                _ => timers
+                    .get_mut(id)
+                    .map(|timer| match command {
+                        "start" => timer.start(),
+                        "stop" => timer.stop(),
+                        _ => timer.reset(),
+                    })
+                    .is_some(),
+            };
+            if !found {
+                println!("No timer {id}.");
+                return;
+            }
+        }
+        _ => {
+            println!("Invalid command.");
+            return;
+        }
+    }
+
+    for id in timers.take_expired() {
+        println!(
+            "Timer {id} ({}) expired!",
+            timers.label(id).unwrap_or_default()
+        );
+    }
+    for id in timers.by_next_expiry() {
+        if let (Some(label), Some(timer)) = (timers.label(id), timers.get(id)) {
+            println!(
+                "{id:<5} {label:<20} {:<8} {}",
+                timer.state(),
+                format_time(timer.remaining())
+            );
+        }
+    }
+}
+
+/// The most rounds an interval-training session can have.
+const MAX_ROUNDS: u32 = 1000;
+
+/// Runs an `intervals` sub-command, which sets up or controls an interval-training session.
+fn intervals_command<'a>(
+    session: &mut Option<Sequence>,
+    command: &str,
+    arguments: impl Iterator<Item = &'a str>,
+) {
+    if let Ok(warmup) = command.parse() {
+        let numbers = arguments.map(str::parse).collect::<Result<Vec<u64>, _>>();
+        let Ok(&[work, rest, rounds, cooldown]) = numbers.as_deref() else {
+            println!("Use `intervals <warmup> <work> <rest> <rounds> <cooldown>`, in seconds, to set up a session.");
+            return;
+        };
+        let total = work
+            .checked_add(rest)
+            .and_then(|round| round.checked_mul(rounds))
+            .and_then(|rounds| rounds.checked_add(warmup))
+            .and_then(|total| total.checked_add(cooldown));
+        let rounds = u32::try_from(rounds)
+            .ok()
+            .filter(|&rounds| rounds <= MAX_ROUNDS);
+        let (Some(_), Some(rounds)) = (total, rounds) else {
+            println!(
+                "The session is too long, use at most {MAX_ROUNDS} rounds and shorter phases."
+            );
+            return;
+        };
+        *session = Some(Sequence::new(Phase::interval_training(
+            Duration::from_secs(warmup),
+            Duration::from_secs(work),
+            Duration::from_secs(rest),
+            rounds,
+            Duration::from_secs(cooldown),
+        )));
+        println!("Session set up, use `intervals start` to start it.");
+        return;
+    }
+
+    let Some(session) = session else {
+        println!("No session yet, use `intervals <warmup> <work> <rest> <rounds> <cooldown>` to set one up.");
+        return;
+    };
+    match command {
+        "start" => session.start(),
+        "stop" => session.stop(),
+        "reset" => session.reset(),
+        "skip" => session.skip_phase(),
+        "repeat" => session.repeat_phase(),
+        "status" => {}
+        _ => {
+            println!("Invalid command.");
+            return;
+        }
+    }
+
+    match (session.current_phase(), session.round()) {
+        (Some(phase), Some(round)) => println!(
+            "{} (round {round} of {}), {} left.",
+            phase.label(),
+            session.rounds(),
+            format_time(session.phase_remaining())
+        ),
+        (Some(phase), None) => println!(
+            "{}, {} left.",
+            phase.label(),
+            format_time(session.phase_remaining())
+        ),
+        (None, _) => println!("Session finished."),
+    }
+    println!(
+        "Session {}, {} remaining.",
+        session.state(),
+        format_time(session.remaining())
+    );
+}
+
+/// Runs a `pomodoro` sub-command, which acts on the Pomodoro timer.
+fn pomodoro_command(pomodoro: &mut Pomodoro, command: &str) {
+    for session in pomodoro.update() {
+        println!("The {session} session is over!");
+    }
+    match command {
+        "start" => pomodoro.start(),
+        "stop" => pomodoro.stop(),
+        "reset" => pomodoro.reset(),
+        "skip" => pomodoro.skip(),
+        "interrupt" => {
+            if !pomodoro.interrupt(InterruptionKind::External) {
+                println!("No focus session to interrupt.");
+            }
+        }
+        "status" => {}
+        _ => {
+            println!("Invalid command.");
+            return;
+        }
+    }
+
+    println!(
+        "Pomodoro {} {}, {} remaining.",
+        pomodoro.session(),
+        pomodoro.state(),
+        format_time(pomodoro.remaining())
+    );
+    println!(
+        "{} pomodoro(s) completed today, {} interruption(s) so far.",
+        pomodoro.completed_today(),
+        pomodoro.interruptions().len()
+    );
+}
+
+/// Runs a `deadline` sub-command, which counts down to a time of day on the local clock.
+fn deadline_command(deadline: &mut Option<DeadlineTimer>, command: &str) {
+    if command != "status" {
+        let Ok(time) = NaiveTime::parse_from_str(command, "%H:%M") else {
+            println!("Use `deadline <HH:MM>` to count down to a time of day.");
+            return;
+        };
+        *deadline = Some(DeadlineTimer::until_local(time));
+    }
+
+    match deadline {
+        Some(deadline) if deadline.is_done() => println!(
+            "Deadline {} passed {} ago.",
+            deadline.deadline().format("%H:%M"),
+            format_time(deadline.signed_remaining().magnitude())
+        ),
+        Some(deadline) => println!(
+            "{} until {}.",
+            format_time(deadline.remaining()),
+            deadline.deadline().format("%a %H:%M")
+        ),
+        None => println!("No deadline yet, use `deadline <HH:MM>` to set one."),
+    }
+}
+
+fn print_timer_status(timer: &Timer) {
+    match timer.state() {
+        TimerState::Expired if timer.is_overtime_enabled() => println!(
+            "Timer expired, {} overdue.",
+            format_signed_time(timer.signed_remaining())
+        ),
+        TimerState::Expired => println!("Timer expired."),
+        state => println!(
+            "Timer {state}, {} remaining.",
+            format_time(timer.remaining())
+        ),
+    }
+    match (timer.completion_policy(), timer.remaining_cycles()) {
+        (CompletionPolicy::RepeatForever, _) => {
+            println!("Cycle {}, repeating forever.", timer.cycle() + 1);
+        }
+        (CompletionPolicy::Repeat(_), Some(cycles)) => {
+            println!("Cycle {}, {cycles} more to go.", timer.cycle() + 1);
+        }
+        _ => {}
+    }
+    if timer.completions() > 0 {
+        println!("Timer completed {} time(s).", timer.completions());
+    }
+}
+
+/// Runs a `race` sub-command, which registers competitors and records their times.
+fn race_command<'a>(race: &mut Race, command: &str, mut arguments: impl Iterator<Item = &'a str>) {
+    let mut number = || {
+        arguments
+            .next()
+            .and_then(|argument| argument.parse::<u32>().ok())
+    };
+    let result = match command {
+        "add" => match number() {
+            Some(bib) => {
+                let name = arguments.collect::<Vec<_>>().join(" ");
+                race.add_competitor(bib.into(), name)
+            }
+            None => {
+                println!("Use `race add <bib> <name>` to register a competitor.");
+                return;
+            }
+        },
+        "start" => {
+            race.start_after(Duration::from_secs(number().unwrap_or_default().into()));
+            Ok(())
+        }
+        "interval" => match number() {
+            Some(secs) => race.set_interval_starts(Duration::from_secs(secs.into())),
+            None => {
+                println!("Use `race interval <secs>` to stagger the starts.");
+                return;
+            }
+        },
+        "split" | "finish" => match number().map(Bib::from) {
+            Some(bib) if command == "split" => race.record_split(bib).map(drop),
+            Some(bib) => race.record_finish(bib).map(drop),
+            None => {
+                println!("Use `race {command} <bib>` to record a {command}.");
+                return;
+            }
+        },
+        "correct" | "remove" => {
+            let record = number().and_then(|record| usize::try_from(record).ok());
+            match (record, command) {
+                (Some(record), "remove") => race.remove_record(record).map(drop),
+                (Some(record), _) => match number() {
+                    Some(bib) => race.correct_bib(record, bib.into()),
+                    None => {
+                        println!("Use `race correct <record> <bib>` to correct a bib.");
+                        return;
+                    }
+                },
+                (None, _) => {
+                    println!("Use `race {command} <record>` to {command} a record.");
+                    return;
+                }
+            }
+        }
+        "records" => {
+            for (index, record) in race.records().iter().enumerate() {
+                println!(
+                    "{index:<5} {:<5} {:<8} {}",
+                    record.bib(),
+                    if record.is_finish() {
+                        "finish"
+                    } else {
+                        "split"
+                    },
+                    format_time(record.race_time())
+                );
+            }
+            return;
+        }
+        "results" => Ok(()),
+        _ => {
+            println!("Invalid command.");
+            return;
+        }
+    };
+    if let Err(err) = result {
+        println!("Could not {command}: {err}.");
+        return;
+    }
+
+    for result in race.results() {
+        println!(
+            "{:<5} {:<5} {:<20} {:<10} {}",
+            result
+                .position()
+                .map_or_else(|| "-".to_string(), |position| position.to_string()),
+            result.bib(),
+            result.name(),
+            result.time().map(format_time).unwrap_or_default(),
+            result
+                .gap()
+                .map(|gap| format!("+{}", format_time(gap)))
+                .unwrap_or_default()
+        );
     }
 }
@@ -13,6 +13,48 @@
 #endif
 
 
+/**
+ * What a [`Timer`] does once it runs out of time.
+ *
+ * The policy is applied by the [`Timer`] itself, so every front-end behaves the same way.
+ */
+typedef enum CompletionPolicy_Tag {
+  /**
+   * The timer stays [`Expired`](TimerState::Expired) with no time remaining until it is reset.
+   */
+  StayExpired,
+  /**
+   * The timer resets itself and goes back to [`Idle`](TimerState::Idle).
+   */
+  AutoReset,
+  /**
+   * The timer starts counting down again this many times before it stays expired, so
+   * `Repeat(2)` runs it three times in total. Every run is scheduled from the moment the timer
+   * was started, so no time is lost between runs.
+   */
+  Repeat,
+  /**
+   * The timer starts counting down again every time it runs out, until it is stopped or reset.
+   * Like with [`Repeat`](CompletionPolicy::Repeat), the runs never drift from the moment the
+   * timer was started.
+   */
+  RepeatForever,
+  /**
+   * The timer stays [`Expired`](TimerState::Expired) but keeps counting past zero, see
+   * [`Timer::signed_remaining`] and [`Timer::overtime`].
+   */
+  Overtime,
+} CompletionPolicy_Tag;
+
+typedef struct CompletionPolicy {
+  CompletionPolicy_Tag tag;
+  union {
+    struct {
+      uint32_t repeat;
+    };
+  };
+} CompletionPolicy;
+
 #if defined(TARGET_OS_OSX)
 /**
  * A buffer that stores `LapTime` values for a stopwatch.
@@ -29,31 +71,135 @@ typedef struct LapTimeBuffer {
 } LapTimeBuffer;
 #endif
 
+/**
+ * Something that happened to a [`Timer`] owned by a [`TimerDriver`].
+ */
+typedef enum TimerEvent_Tag {
+  /**
+   * The timer was started for the first time since it was created or last reset.
+   */
+  TimerEvent_Started,
+  /**
+   * The timer was stopped before it expired.
+   */
+  TimerEvent_Paused,
+  /**
+   * The timer was started again after it was paused or given more time.
+   */
+  TimerEvent_Resumed,
+  /**
+   * The timer was reset.
+   */
+  TimerEvent_Reset,
+  /**
+   * The timer ran out of time. This is emitted once for every run that completes, including runs
+   * that are repeated or reset by the [`CompletionPolicy`](crate::timer::CompletionPolicy).
+   */
+  TimerEvent_Expired,
+  /**
+   * The timer reached the warning threshold at this index in [`Timer::warnings`].
+   */
+  TimerEvent_Warning,
+} TimerEvent_Tag;
+
+typedef struct TimerEvent {
+  TimerEvent_Tag tag;
+  union {
+    struct {
+      size_t warning;
+    };
+  };
+} TimerEvent;
+
+#if defined(TARGET_OS_OSX)
+/**
+ * A buffer of timer ids returned by a timer set, freed with `timer_set_TimerIdBuffer_free`.
+ */
+typedef struct TimerIdBuffer {
+  /**
+   * A pointer to the ids stored in the buffer.
+   */
+  uint64_t *data;
+  /**
+   * The length of the buffer.
+   */
+  size_t len;
+} TimerIdBuffer;
+#endif
+
+/**
+ * The state of a [`Timer`].
+ *
+ * A [`Timer`] starts out [`Idle`](TimerState::Idle), alternates between
+ * [`Running`](TimerState::Running) and [`Paused`](TimerState::Paused) as it is started and
+ * stopped, becomes [`Expired`](TimerState::Expired) once no time remains, and returns to
+ * [`Idle`](TimerState::Idle) when it is reset.
+ */
+typedef enum TimerState {
+  /**
+   * The timer has not been started since it was created or last reset.
+   */
+  Idle,
+  /**
+   * The timer is counting down.
+   */
+  Running,
+  /**
+   * The timer was started and then stopped before it expired.
+   */
+  Paused,
+  /**
+   * The timer ran out of time.
+   */
+  Expired,
+} TimerState;
+
+#if defined(TARGET_OS_OSX)
+/**
+ * A buffer of warning threshold indices returned by a timer, freed with
+ * `timer_WarningBuffer_free`.
+ */
+typedef struct WarningBuffer {
+  /**
+   * A pointer to the indices stored in the buffer.
+   */
+  size_t *data;
+  /**
+   * The length of the buffer.
+   */
+  size_t len;
+} WarningBuffer;
+#endif
+
 #if defined(TARGET_OS_OSX)
 /**
  * # Safety
  *
  * This function dereferences a raw pointer
  */
-uint64_t rust_Duration_asSecs(const void *duration);
+void driver_TimerDriver_addTime(void *driver, uint64_t secs);
 #endif
 
 #if defined(TARGET_OS_OSX)
 /**
+ * Makes the driver emit `TimerEvent_Warning` once `percent` percent of the duration remains.
+ *
  * # Safety
  *
  * This function dereferences a raw pointer
  */
-double rust_Duration_asSecsF64(const void *duration);
+void driver_TimerDriver_addWarningPercent(void *driver, uint8_t percent);
 #endif
 
 #if defined(TARGET_OS_OSX)
 /**
+ * Makes the driver emit `TimerEvent_Warning` once `secs` seconds remain.
+ *
  * # Safety
  *
  * This function dereferences a raw pointer
  */
-bool rust_Duration_eq(const void *duration, const void *other);
+void driver_TimerDriver_addWarningSecs(void *driver, uint64_t secs);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -62,7 +208,7 @@ bool rust_Duration_eq(const void *duration, const void *other);
  *
  * This function dereferences a raw pointer
  */
-void rust_Duration_free(void *duration);
+void driver_TimerDriver_clearWarnings(void *driver);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -71,7 +217,40 @@ void rust_Duration_free(void *duration);
  *
  * This function dereferences a raw pointer
  */
-bool rust_Duration_isZero(const void *duration);
+void *driver_TimerDriver_create(uint64_t secs);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer and frees the object
+ */
+void driver_TimerDriver_free(void *driver);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Creates a driver that takes over a timer, e.g. one restored with `timer_Timer_restoreJson`.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer and takes ownership of the timer, which must not be
+ * used or freed afterwards
+ */
+void *driver_TimerDriver_fromTimer(void *timer);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Calls `callback` with `context` for every event of the driver, on a background thread.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer, and `context` must be usable from any thread until
+ * the driver is freed
+ */
+void driver_TimerDriver_onEvent(void *driver, void (*callback)(TimerEvent, void*), void *context);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -80,7 +259,7 @@ bool rust_Duration_isZero(const void *duration);
  *
  * This function dereferences a raw pointer
  */
-void stopwatch_LapTime_free(void *laptime);
+void *driver_TimerDriver_remaining(void *driver);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -89,7 +268,7 @@ void stopwatch_LapTime_free(void *laptime);
  *
  * This function dereferences a raw pointer
  */
-void *stopwatch_LapTime_getIndex(struct LapTimeBuffer buf, size_t index);
+void driver_TimerDriver_reset(void *driver);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -98,7 +277,7 @@ void *stopwatch_LapTime_getIndex(struct LapTimeBuffer buf, size_t index);
  *
  * This function dereferences a raw pointer
  */
-size_t stopwatch_LapTime_lapNumber(void *laptime);
+void driver_TimerDriver_setCompletionPolicy(void *driver, struct CompletionPolicy policy);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -107,7 +286,7 @@ size_t stopwatch_LapTime_lapNumber(void *laptime);
  *
  * This function dereferences a raw pointer
  */
-void *stopwatch_LapTime_splitTime(void *laptime);
+void driver_TimerDriver_setDuration(void *driver, uint64_t secs);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -116,7 +295,7 @@ void *stopwatch_LapTime_splitTime(void *laptime);
  *
  * This function dereferences a raw pointer
  */
-void *stopwatch_LapTime_totalTime(void *laptime);
+void driver_TimerDriver_setOvertime(void *driver, bool enabled);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -125,14 +304,16 @@ void *stopwatch_LapTime_totalTime(void *laptime);
  *
  * This function dereferences a raw pointer
  */
-void stopwatch_Stopwatch_addLap(void *stopwatch);
+void driver_TimerDriver_setRemaining(void *driver, uint64_t secs);
 #endif
 
 #if defined(TARGET_OS_OSX)
 /**
- * A constructor that creates a new Stopwatch with default values.
+ * # Safety
+ *
+ * This function dereferences a raw pointer
  */
-void *stopwatch_Stopwatch_create(void);
+void driver_TimerDriver_start(void *driver);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -141,16 +322,16 @@ void *stopwatch_Stopwatch_create(void);
  *
  * This function dereferences a raw pointer
  */
-void *stopwatch_Stopwatch_elapsedTime(void *stopwatch);
+TimerState driver_TimerDriver_state(void *driver);
 #endif
 
 #if defined(TARGET_OS_OSX)
 /**
  * # Safety
  *
- * This function dereferences a raw pointer and frees the object
+ * This function dereferences a raw pointer
  */
-void stopwatch_Stopwatch_free(void *stopwatch);
+void driver_TimerDriver_stop(void *driver);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -159,16 +340,18 @@ void stopwatch_Stopwatch_free(void *stopwatch);
  *
  * This function dereferences a raw pointer
  */
-void stopwatch_Stopwatch_freeLapTimes(struct LapTimeBuffer buf);
+void driver_TimerDriver_subtractTime(void *driver, uint64_t secs);
 #endif
 
 #if defined(TARGET_OS_OSX)
 /**
+ * Returns a copy of the timer as it is now, freed with `timer_Timer_free`.
+ *
  * # Safety
  *
  * This function dereferences a raw pointer
  */
-bool stopwatch_Stopwatch_isRunning(void *stopwatch);
+void *driver_TimerDriver_timer(void *driver);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -177,7 +360,7 @@ bool stopwatch_Stopwatch_isRunning(void *stopwatch);
  *
  * This function dereferences a raw pointer
  */
-struct LapTimeBuffer stopwatch_Stopwatch_lapTimes(void *stopwatch);
+uint64_t rust_Duration_asSecs(const void *duration);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -186,7 +369,7 @@ struct LapTimeBuffer stopwatch_Stopwatch_lapTimes(void *stopwatch);
  *
  * This function dereferences a raw pointer
  */
-void stopwatch_Stopwatch_reset(void *stopwatch);
+double rust_Duration_asSecsF64(const void *duration);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -195,7 +378,7 @@ void stopwatch_Stopwatch_reset(void *stopwatch);
  *
  * This function dereferences a raw pointer
  */
-void stopwatch_Stopwatch_start(void *stopwatch);
+bool rust_Duration_eq(const void *duration, const void *other);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -204,7 +387,7 @@ void stopwatch_Stopwatch_start(void *stopwatch);
  *
  * This function dereferences a raw pointer
  */
-void stopwatch_Stopwatch_stop(void *stopwatch);
+void rust_Duration_free(void *duration);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -213,7 +396,16 @@ void stopwatch_Stopwatch_stop(void *stopwatch);
  *
  * This function dereferences a raw pointer
  */
-const char *stopwatch_formatTime(void *duration);
+bool rust_Duration_isZero(const void *duration);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer and frees the string
+ */
+void rust_String_free(char *string);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -222,7 +414,7 @@ const char *stopwatch_formatTime(void *duration);
  *
  * This function dereferences a raw pointer
  */
-void *timer_Timer_create(uint64_t secs);
+void stopwatch_LapTime_free(void *laptime);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -231,16 +423,18 @@ void *timer_Timer_create(uint64_t secs);
  *
  * This function dereferences a raw pointer
  */
-void *timer_Timer_duration(void *timer);
+void *stopwatch_LapTime_getIndex(struct LapTimeBuffer buf, size_t index);
 #endif
 
 #if defined(TARGET_OS_OSX)
 /**
+ * Returns the label of a lap, or null if it has none. The label is freed with `rust_String_free`.
+ *
  * # Safety
  *
  * This function dereferences a raw pointer
  */
-void *timer_Timer_elapsed(void *timer);
+char *stopwatch_LapTime_label(void *laptime);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -249,25 +443,30 @@ void *timer_Timer_elapsed(void *timer);
  *
  * This function dereferences a raw pointer
  */
-bool timer_Timer_eq(void *timer, void *other);
+size_t stopwatch_LapTime_lapNumber(void *laptime);
 #endif
 
 #if defined(TARGET_OS_OSX)
 /**
+ * Returns the note of a lap, or null if it has none. The note is freed with `rust_String_free`.
+ *
  * # Safety
  *
- * This function dereferences a raw pointer and frees the object
+ * This function dereferences a raw pointer
  */
-void timer_Timer_free(void *timer);
+char *stopwatch_LapTime_note(void *laptime);
 #endif
 
 #if defined(TARGET_OS_OSX)
 /**
+ * Returns the time of day a lap was taken in ISO 8601 format, or null if it is not known. The
+ * string is freed with `rust_String_free`.
+ *
  * # Safety
  *
  * This function dereferences a raw pointer
  */
-bool timer_Timer_hasNotStarted(void *timer);
+char *stopwatch_LapTime_recordedAt(void *laptime);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -276,7 +475,7 @@ bool timer_Timer_hasNotStarted(void *timer);
  *
  * This function dereferences a raw pointer
  */
-bool timer_Timer_isDone(void *timer);
+void *stopwatch_LapTime_splitTime(void *laptime);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -285,7 +484,7 @@ bool timer_Timer_isDone(void *timer);
  *
  * This function dereferences a raw pointer
  */
-bool timer_Timer_isRunning(void *timer);
+void *stopwatch_LapTime_totalTime(void *laptime);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -294,25 +493,36 @@ bool timer_Timer_isRunning(void *timer);
  *
  * This function dereferences a raw pointer
  */
-void *timer_Timer_remaining(void *timer);
+void stopwatch_Stopwatch_addLap(void *stopwatch);
 #endif
 
 #if defined(TARGET_OS_OSX)
 /**
+ * Cancels a delayed start. Returns false if the stopwatch was not counting down.
+ *
  * # Safety
  *
  * This function dereferences a raw pointer
  */
-void timer_Timer_reset(void *timer);
+bool stopwatch_Stopwatch_cancelCountdown(void *stopwatch);
 #endif
 
 #if defined(TARGET_OS_OSX)
 /**
+ * Returns the time left until a delayed start, or null if the stopwatch is not counting down.
+ *
  * # Safety
  *
  * This function dereferences a raw pointer
  */
-void timer_Timer_start(void *timer);
+void *stopwatch_Stopwatch_countdownRemaining(void *stopwatch);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * A constructor that creates a new Stopwatch with default values.
+ */
+void *stopwatch_Stopwatch_create(void);
 #endif
 
 #if defined(TARGET_OS_OSX)
@@ -321,5 +531,724 @@ void timer_Timer_start(void *timer);
  *
  * This function dereferences a raw pointer
  */
-void timer_Timer_stop(void *timer);
+void *stopwatch_Stopwatch_elapsedTime(void *stopwatch);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Exports the stopwatch session, with its segments and laps, as CSV.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+char *stopwatch_Stopwatch_exportCsv(void *stopwatch);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Exports the stopwatch session, with its segments and laps, as Markdown tables.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+char *stopwatch_Stopwatch_exportMarkdown(void *stopwatch);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Returns the number of the fastest lap, or 0 if there are no laps.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+size_t stopwatch_Stopwatch_fastestLap(void *stopwatch);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer and frees the object
+ */
+void stopwatch_Stopwatch_free(void *stopwatch);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void stopwatch_Stopwatch_freeLapTimes(struct LapTimeBuffer buf);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Inserts a lap that ended `secs` seconds after the start of the stopwatch. Returns false if that
+ * is past the elapsed time of the stopwatch.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+bool stopwatch_Stopwatch_insertLap(void *stopwatch, double secs);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+bool stopwatch_Stopwatch_isRunning(void *stopwatch);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Returns how many seconds longer than the mean split time the lap numbered `lap_number` took,
+ * which is negative for laps faster than the mean and 0 if there is no such lap.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+double stopwatch_Stopwatch_lapDeltaFromMean(void *stopwatch, size_t lap_number);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Returns the mean split time of the laps, or null if there are no laps.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void *stopwatch_Stopwatch_lapMean(void *stopwatch);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Returns the median split time of the laps, or null if there are no laps.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void *stopwatch_Stopwatch_lapMedian(void *stopwatch);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Returns the mean split time of the last `count` laps, or null if there are no laps or `count`
+ * is 0.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void *stopwatch_Stopwatch_lapRollingAverage(void *stopwatch, size_t count);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Returns the standard deviation of the split times of the laps, or null if there are no laps.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void *stopwatch_Stopwatch_lapStandardDeviation(void *stopwatch);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+struct LapTimeBuffer stopwatch_Stopwatch_lapTimes(void *stopwatch);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Returns the wall-clock time the stopwatch spent stopped since it was first started.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void *stopwatch_Stopwatch_pausedTime(void *stopwatch);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Removes a lap and merges its time into the lap that follows it. Returns false if there is no
+ * such lap.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+bool stopwatch_Stopwatch_removeLap(void *stopwatch, size_t lap_number);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void stopwatch_Stopwatch_reset(void *stopwatch);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Creates a stopwatch from a snapshot returned by `stopwatch_Stopwatch_snapshotJson`, or returns
+ * null if `json` is not a valid snapshot. A stopwatch that was running keeps running, with the
+ * time that passed since the snapshot counted as elapsed.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void *stopwatch_Stopwatch_restoreJson(const char *json);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Sets the label of a lap, or removes it if `label` is null. Returns false if there is no such
+ * lap.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer, and `label` must be null or a valid C string
+ */
+bool stopwatch_Stopwatch_setLapLabel(void *stopwatch, size_t lap_number, const char *label);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Sets the note of a lap, or removes it if `note` is null. Returns false if there is no such lap.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer, and `note` must be null or a valid C string
+ */
+bool stopwatch_Stopwatch_setLapNote(void *stopwatch, size_t lap_number, const char *note);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Returns the number of the slowest lap, or 0 if there are no laps.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+size_t stopwatch_Stopwatch_slowestLap(void *stopwatch);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Returns a snapshot of the stopwatch, with its laps, as JSON, to be restored with
+ * `stopwatch_Stopwatch_restoreJson` after the app restarts. The string is freed with
+ * `rust_String_free`, and is null if the snapshot could not be serialized.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+char *stopwatch_Stopwatch_snapshotJson(void *stopwatch);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void stopwatch_Stopwatch_start(void *stopwatch);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Starts the stopwatch once `secs` seconds have passed, with its zero at the end of the countdown.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void stopwatch_Stopwatch_startAfter(void *stopwatch, double secs);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void stopwatch_Stopwatch_stop(void *stopwatch);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+const char *stopwatch_formatTime(void *duration);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void timer_Timer_addTime(void *timer, uint64_t secs);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void timer_Timer_addWarningPercent(void *timer, uint8_t percent);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void timer_Timer_addWarningSecs(void *timer, uint64_t secs);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void timer_Timer_clearWarnings(void *timer);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+struct CompletionPolicy timer_Timer_completionPolicy(void *timer);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+uint32_t timer_Timer_completions(void *timer);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void *timer_Timer_create(uint64_t secs);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+uint32_t timer_Timer_cycle(void *timer);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void *timer_Timer_duration(void *timer);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void *timer_Timer_elapsed(void *timer);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+bool timer_Timer_eq(void *timer, void *other);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer and frees the object
+ */
+void timer_Timer_free(void *timer);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+bool timer_Timer_hasNotStarted(void *timer);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+bool timer_Timer_isDone(void *timer);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+bool timer_Timer_isOvertimeEnabled(void *timer);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+bool timer_Timer_isRunning(void *timer);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Returns how long the timer runs until it reaches the next threshold that was not reported yet,
+ * or null if it is not running or has no such threshold left.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void *timer_Timer_nextWarning(void *timer);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void *timer_Timer_overtime(void *timer);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void *timer_Timer_remaining(void *timer);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void timer_Timer_reset(void *timer);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Creates a timer from a snapshot returned by `timer_Timer_snapshotJson`, or returns null if
+ * `json` is not a valid snapshot. A timer that was running keeps running, with the time that
+ * passed since the snapshot counted as elapsed.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void *timer_Timer_restoreJson(const char *json);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void timer_Timer_setCompletionPolicy(void *timer, struct CompletionPolicy policy);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void timer_Timer_setDuration(void *timer, uint64_t secs);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void timer_Timer_setOvertime(void *timer, bool enabled);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void timer_Timer_setRemaining(void *timer, uint64_t secs);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Returns a snapshot of the timer as JSON, to be restored with `timer_Timer_restoreJson` after
+ * the app restarts. The string is freed with `rust_String_free`, and is null if the snapshot could
+ * not be serialized.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+char *timer_Timer_snapshotJson(void *timer);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void timer_Timer_start(void *timer);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+enum TimerState timer_Timer_state(void *timer);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void timer_Timer_stop(void *timer);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void timer_Timer_subtractTime(void *timer, uint64_t secs);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Returns the indices of the thresholds the timer reached since they were last reported, in the
+ * order they were added, and marks them as reported.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+WarningBuffer timer_Timer_takeWarnings(void *timer);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function frees a buffer returned by a timer
+ */
+void timer_WarningBuffer_free(WarningBuffer buf);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function frees a buffer returned by a timer set
+ */
+void timer_set_TimerIdBuffer_free(struct TimerIdBuffer buf);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer, and `label` must be a valid C string
+ */
+uint64_t timer_set_TimerSet_add(void *set, const char *label, uint64_t secs);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+struct TimerIdBuffer timer_set_TimerSet_byNextExpiry(void *set);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * A constructor that creates a new empty `TimerSet`.
+ */
+void *timer_set_TimerSet_create(void);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer and frees the object
+ */
+void timer_set_TimerSet_free(void *set);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Returns the label of a timer, or null if there is no such timer. The label is freed with
+ * `rust_String_free`.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+char *timer_set_TimerSet_label(void *set, uint64_t id);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void timer_set_TimerSet_pauseAll(void *set);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Returns the remaining time of a timer, or null if there is no such timer.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void *timer_set_TimerSet_remaining(void *set, uint64_t id);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+bool timer_set_TimerSet_remove(void *set, uint64_t id);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+bool timer_set_TimerSet_reset(void *set, uint64_t id);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void timer_set_TimerSet_resumeAll(void *set);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+bool timer_set_TimerSet_start(void *set, uint64_t id);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+void timer_set_TimerSet_startAll(void *set);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * Returns the state of a timer, which is `Idle` if there is no such timer.
+ *
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+TimerState timer_set_TimerSet_state(void *set, uint64_t id);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+bool timer_set_TimerSet_stop(void *set, uint64_t id);
+#endif
+
+#if defined(TARGET_OS_OSX)
+/**
+ * # Safety
+ *
+ * This function dereferences a raw pointer
+ */
+struct TimerIdBuffer timer_set_TimerSet_takeExpired(void *set);
 #endif
@@ -0,0 +1,245 @@
+//! A module that compares the laps of a stopwatch against a reference run
+//!
+//! A reference run, or ghost, is the list of laps of a previous session or of a target plan. Each
+//! live lap is compared against the reference lap with the same number, both on its own split time
+//! and on the total time so far, and the finish time of the live run is projected from how far
+//! ahead or behind the reference it is.
+
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::duration::SignedDuration;
+use crate::stopwatch::LapTime;
+
+/// The laps of a previous session or of a target plan that live laps are compared against.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use clock::comparison::ReferenceRun;
+/// use clock::stopwatch::{format_delta, LapTime};
+///
+/// let plan = ReferenceRun::from_splits([Duration::from_secs(60); 4]);
+/// let laps = [
+///     LapTime::new(1, Duration::from_millis(61_230), Duration::from_millis(61_230)),
+///     LapTime::new(2, Duration::from_millis(58_370), Duration::from_millis(119_600)),
+/// ];
+///
+/// let comparisons = plan.compare(&laps);
+/// assert_eq!(format_delta(comparisons[0].split_delta()), "+0:01.23");
+/// assert_eq!(format_delta(comparisons[1].split_delta()), "-0:01.63");
+/// assert_eq!(format_delta(comparisons[1].cumulative_delta()), "-0:00.40");
+///
+/// assert_eq!(
+///     plan.projected_finish(&laps, Duration::from_millis(119_600)),
+///     Some(Duration::from_millis(239_600))
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ReferenceRun {
+    /// The laps of the reference run, in order.
+    laps: Vec<LapTime>,
+}
+
+impl ReferenceRun {
+    /// Creates a reference run from the laps of a previous session, e.g. the
+    /// [`lap_times`](crate::stopwatch::Stopwatch::lap_times) of a stopwatch.
+    #[must_use]
+    pub fn from_laps(laps: &[LapTime]) -> Self {
+        Self {
+            laps: laps.to_vec(),
+        }
+    }
+
+    /// Creates a reference run from a plan giving the split time of every lap.
+    #[must_use]
+    pub fn from_splits(splits: impl IntoIterator<Item = Duration>) -> Self {
+        let mut total_time = Duration::ZERO;
+        let laps = splits
+            .into_iter()
+            .enumerate()
+            .map(|(index, split_time)| {
+                total_time += split_time;
+                LapTime::new(index + 1, split_time, total_time)
+            })
+            .collect();
+        Self { laps }
+    }
+
+    /// Returns the laps of the reference run.
+    #[must_use]
+    pub fn laps(&self) -> &[LapTime] {
+        &self.laps
+    }
+
+    /// Returns the total time of the reference run, or `None` if it has no laps.
+    #[must_use]
+    pub fn finish(&self) -> Option<Duration> {
+        self.laps.last().map(LapTime::total_time)
+    }
+
+    /// Compares each of `laps` against the reference lap with the same position. Laps past the end
+    /// of the reference run are not compared.
+    #[must_use]
+    pub fn compare(&self, laps: &[LapTime]) -> Vec<LapComparison> {
+        laps.iter()
+            .zip(&self.laps)
+            .map(|(lap, reference)| LapComparison {
+                lap: lap.clone(),
+                reference: reference.clone(),
+            })
+            .collect()
+    }
+
+    /// Projects the finish time of a run that recorded `laps` and has been running for `elapsed`,
+    /// assuming it keeps the pace of the reference from now on. Returns `None` if the reference run
+    /// has no laps.
+    ///
+    /// The projection is the finish of the reference shifted by the cumulative delta of the last
+    /// compared lap. If the lap in progress already took longer than its reference split, the
+    /// excess is added too, so the projection moves as soon as the run falls behind.
+    #[must_use]
+    pub fn projected_finish(&self, laps: &[LapTime], elapsed: Duration) -> Option<Duration> {
+        let finish = self.finish()?;
+        let compared = laps.len().min(self.laps.len());
+        let (delta, last_total_time) = match compared.checked_sub(1) {
+            Some(index) => (
+                SignedDuration::difference(laps[index].total_time(), self.laps[index].total_time()),
+                laps[index].total_time(),
+            ),
+            None => (SignedDuration::ZERO, Duration::ZERO),
+        };
+        let excess = self.laps.get(compared).map_or(Duration::ZERO, |next| {
+            elapsed
+                .saturating_sub(last_total_time)
+                .saturating_sub(next.split_time())
+        });
+
+        let projected = SignedDuration::positive(finish + excess) + delta;
+        let projected = if projected.is_negative() {
+            Duration::ZERO
+        } else {
+            projected.magnitude()
+        };
+        Some(projected.max(elapsed))
+    }
+}
+
+/// A lap compared against the lap with the same position in a [`ReferenceRun`], see
+/// [`ReferenceRun::compare`].
+///
+/// A positive delta means the lap was slower than the reference, a negative one that it was
+/// faster.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LapComparison {
+    /// The lap being compared.
+    lap: LapTime,
+    /// The reference lap it is compared against.
+    reference: LapTime,
+}
+
+impl LapComparison {
+    /// Returns the lap being compared.
+    #[must_use]
+    pub fn lap(&self) -> &LapTime {
+        &self.lap
+    }
+
+    /// Returns the reference lap it is compared against.
+    #[must_use]
+    pub fn reference(&self) -> &LapTime {
+        &self.reference
+    }
+
+    /// Returns how much longer the split time of the lap was than that of the reference lap.
+    #[must_use]
+    pub fn split_delta(&self) -> SignedDuration {
+        SignedDuration::difference(self.lap.split_time(), self.reference.split_time())
+    }
+
+    /// Returns how much longer the run took up to the end of the lap than the reference did.
+    #[must_use]
+    pub fn cumulative_delta(&self) -> SignedDuration {
+        SignedDuration::difference(self.lap.total_time(), self.reference.total_time())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReferenceRun;
+    use crate::duration::SignedDuration;
+    use crate::stopwatch::Stopwatch;
+    use crate::time_source::MockTimeSource;
+
+    use std::time::Duration;
+
+    #[test]
+    fn test_compare_against_previous_session() {
+        let clock = MockTimeSource::new();
+        let mut stopwatch = Stopwatch::with_time_source(clock.clone());
+        stopwatch.start();
+        for secs in [30, 40, 30] {
+            clock.advance(Duration::from_secs(secs));
+            stopwatch.add_lap();
+        }
+        let reference = ReferenceRun::from_laps(stopwatch.lap_times());
+        assert_eq!(reference.finish(), Some(Duration::from_secs(100)));
+
+        stopwatch.reset();
+        stopwatch.start();
+        for secs in [28, 45] {
+            clock.advance(Duration::from_secs(secs));
+            stopwatch.add_lap();
+        }
+
+        let comparisons = reference.compare(stopwatch.lap_times());
+        assert_eq!(comparisons.len(), 2);
+        assert_eq!(
+            comparisons[0].split_delta(),
+            SignedDuration::negative(Duration::from_secs(2))
+        );
+        assert_eq!(
+            comparisons[0].cumulative_delta(),
+            comparisons[0].split_delta()
+        );
+        assert_eq!(
+            comparisons[1].split_delta(),
+            SignedDuration::positive(Duration::from_secs(5))
+        );
+        assert_eq!(
+            comparisons[1].cumulative_delta(),
+            SignedDuration::positive(Duration::from_secs(3))
+        );
+
+        let projected = |stopwatch: &Stopwatch<MockTimeSource>| {
+            reference.projected_finish(stopwatch.lap_times(), stopwatch.elapsed_time())
+        };
+        assert_eq!(projected(&stopwatch), Some(Duration::from_secs(103)));
+        clock.advance(Duration::from_secs(35));
+        assert_eq!(projected(&stopwatch), Some(Duration::from_secs(108)));
+
+        stopwatch.add_lap();
+        clock.advance(Duration::from_secs(10));
+        stopwatch.add_lap();
+        assert_eq!(reference.compare(stopwatch.lap_times()).len(), 3);
+        assert_eq!(projected(&stopwatch), Some(Duration::from_secs(118)));
+    }
+
+    #[test]
+    fn test_empty_reference() {
+        let reference = ReferenceRun::from_splits([]);
+        assert_eq!(reference.finish(), None);
+        assert_eq!(reference.projected_finish(&[], Duration::ZERO), None);
+
+        let plan = ReferenceRun::from_splits([Duration::from_mins(1); 2]);
+        assert_eq!(
+            plan.projected_finish(&[], Duration::from_secs(10)),
+            Some(Duration::from_mins(2))
+        );
+        assert!(plan.compare(&[]).is_empty());
+    }
+}
@@ -0,0 +1,286 @@
+//! A module that defines timers counting down to a wall-clock time
+//!
+//! Unlike a [`Timer`](crate::timer::Timer), which measures a [`Duration`] on the monotonic clock,
+//! a [`DeadlineTimer`] counts down to a point in time such as "17:30 local". Its remaining time is
+//! recomputed from the wall clock every time it is read, so it stays correct across a suspend and
+//! resume of the machine or a correction of the system time.
+
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Local, LocalResult, NaiveTime, Offset, TimeZone, Utc};
+
+use crate::duration::SignedDuration;
+use crate::time_source::{SystemTimeSource, TimeSource};
+use crate::timer::TimerState;
+
+/// How many days before a skipped local time [`next_occurrence`] looks for the offset in effect
+/// before the clocks were changed.
+const MAX_GAP_DAYS: i64 = 3;
+
+/// Returns the next time after `now` at which the clocks in `tz` show `time`.
+///
+/// If the clocks skip `time` on that day, e.g. when daylight saving time starts, the deadline is
+/// the moment they would have shown it had they not been changed. If the clocks show `time` twice,
+/// e.g. when daylight saving time ends, the deadline is the first of the two still after `now`.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{FixedOffset, NaiveTime, TimeZone};
+/// use clock::deadline::next_occurrence;
+///
+/// let tz = FixedOffset::east_opt(3600).unwrap();
+/// let now = tz.with_ymd_and_hms(2024, 3, 1, 18, 0, 0).unwrap();
+/// let time = NaiveTime::from_hms_opt(17, 30, 0).unwrap();
+///
+/// assert_eq!(
+///     next_occurrence(time, &tz, now.into()),
+///     tz.with_ymd_and_hms(2024, 3, 2, 17, 30, 0).unwrap()
+/// );
+/// ```
+#[must_use]
+pub fn next_occurrence<Tz: TimeZone>(time: NaiveTime, tz: &Tz, now: SystemTime) -> DateTime<Tz> {
+    let now = DateTime::<Utc>::from(now);
+    let mut date = now.with_timezone(tz).date_naive();
+    loop {
+        let local = date.and_time(time);
+        let deadline = match tz.from_local_datetime(&local) {
+            LocalResult::Single(deadline) => deadline,
+            // The first time the clocks show `time` counts, unless it has already passed.
+            LocalResult::Ambiguous(earlier, later) => {
+                if earlier > now {
+                    earlier
+                } else {
+                    later
+                }
+            }
+            LocalResult::None => {
+                // The offset in effect before the gap is that of an earlier time the clocks did
+                // show, since gaps are far shorter than a day. Time zones that skip whole days
+                // fall back to the offset in effect now.
+                let offset = (1..=MAX_GAP_DAYS)
+                    .find_map(|days| {
+                        match tz.from_local_datetime(&(local - chrono::Duration::days(days))) {
+                            LocalResult::Single(earlier) | LocalResult::Ambiguous(earlier, _) => {
+                                Some(earlier.offset().fix())
+                            }
+                            LocalResult::None => None,
+                        }
+                    })
+                    .unwrap_or_else(|| tz.offset_from_utc_datetime(&now.naive_utc()).fix());
+                tz.from_utc_datetime(
+                    &(local - chrono::Duration::seconds(offset.local_minus_utc().into())),
+                )
+            }
+        };
+        if deadline > now {
+            return deadline;
+        }
+        match date.succ_opt() {
+            Some(next) => date = next,
+            None => return deadline,
+        }
+    }
+}
+
+/// A timer that counts down to a wall-clock time.
+///
+/// A [`DeadlineTimer`] cannot be paused: it is [`Running`](TimerState::Running) until its
+/// deadline and [`Expired`](TimerState::Expired) from then on.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use chrono::{TimeZone, Utc};
+/// use clock::deadline::DeadlineTimer;
+/// use clock::time_source::{MockTimeSource, TimeSource};
+///
+/// let clock = MockTimeSource::starting_at(Utc.with_ymd_and_hms(2024, 3, 1, 17, 0, 0).unwrap().into());
+/// let timer = DeadlineTimer::with_time_source(
+///     Utc.with_ymd_and_hms(2024, 3, 1, 17, 30, 0).unwrap(),
+///     clock.clone(),
+/// );
+/// assert_eq!(timer.remaining(), Duration::from_secs(30 * 60));
+///
+/// // The machine sleeps for ten minutes, during which the monotonic clock stands still.
+/// clock.set_wall_clock(clock.wall_clock() + Duration::from_secs(10 * 60));
+///
+/// assert_eq!(timer.remaining(), Duration::from_secs(20 * 60));
+/// ```
+#[derive(Debug, Clone)]
+pub struct DeadlineTimer<Tz: TimeZone = Local, T: TimeSource = SystemTimeSource> {
+    /// The time the timer counts down to.
+    deadline: DateTime<Tz>,
+    /// The [`TimeSource`] the timer reads the wall clock from.
+    time_source: T,
+}
+
+impl<Tz: TimeZone> DeadlineTimer<Tz> {
+    /// Creates a new [`DeadlineTimer`] that counts down to `deadline` on the system clock.
+    #[must_use]
+    pub fn new(deadline: DateTime<Tz>) -> Self {
+        Self::with_time_source(deadline, SystemTimeSource)
+    }
+}
+
+impl DeadlineTimer {
+    /// Creates a new [`DeadlineTimer`] that counts down to the next time the local clock shows
+    /// `time`, e.g. "until 17:30".
+    #[must_use]
+    pub fn until_local(time: NaiveTime) -> Self {
+        Self::new(next_occurrence(time, &Local, SystemTime::now()))
+    }
+}
+
+impl<Tz: TimeZone, T: TimeSource> DeadlineTimer<Tz, T> {
+    /// Creates a new [`DeadlineTimer`] that counts down to `deadline` on the wall clock of
+    /// `time_source`.
+    #[must_use]
+    pub fn with_time_source(deadline: DateTime<Tz>, time_source: T) -> Self {
+        Self {
+            deadline,
+            time_source,
+        }
+    }
+
+    /// Returns the [`TimeSource`] the timer reads the wall clock from.
+    #[must_use]
+    pub fn time_source(&self) -> &T {
+        &self.time_source
+    }
+
+    /// Returns the time the timer counts down to.
+    #[must_use]
+    pub fn deadline(&self) -> &DateTime<Tz> {
+        &self.deadline
+    }
+
+    /// Changes the time the timer counts down to.
+    pub fn set_deadline(&mut self, deadline: DateTime<Tz>) {
+        self.deadline = deadline;
+    }
+
+    /// Returns the time left until the deadline, or zero once it has passed.
+    #[must_use]
+    pub fn remaining(&self) -> Duration {
+        self.until_deadline().unwrap_or_default()
+    }
+
+    /// Returns the time left until the deadline, which is negative once it has passed.
+    #[must_use]
+    pub fn signed_remaining(&self) -> SignedDuration {
+        match self.until_deadline() {
+            Ok(remaining) => SignedDuration::positive(remaining),
+            Err(overdue) => SignedDuration::negative(overdue),
+        }
+    }
+
+    /// Returns [`TimerState::Running`] until the deadline and [`TimerState::Expired`] from then on.
+    #[must_use]
+    pub fn state(&self) -> TimerState {
+        if self.is_done() {
+            TimerState::Expired
+        } else {
+            TimerState::Running
+        }
+    }
+
+    /// Returns `true` once the deadline has been reached.
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    /// Returns the time left until the deadline, or how long ago it passed as the error.
+    fn until_deadline(&self) -> Result<Duration, Duration> {
+        SystemTime::from(self.deadline.clone())
+            .duration_since(self.time_source.wall_clock())
+            .map_err(|overdue| overdue.duration())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{next_occurrence, DeadlineTimer};
+    use crate::time_source::{MockTimeSource, TimeSource};
+    use crate::timer::TimerState;
+
+    use chrono::{FixedOffset, NaiveTime, TimeZone, Utc};
+    use std::time::Duration;
+
+    #[test]
+    fn test_follows_wall_clock() {
+        let clock =
+            MockTimeSource::starting_at(Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap().into());
+        let mut timer = DeadlineTimer::with_time_source(
+            Utc.with_ymd_and_hms(2024, 3, 1, 13, 0, 0).unwrap(),
+            clock.clone(),
+        );
+        assert_eq!(timer.remaining(), Duration::from_hours(1));
+        assert_eq!(timer.state(), TimerState::Running);
+
+        clock.advance(Duration::from_mins(15));
+        assert_eq!(timer.remaining(), Duration::from_mins(45));
+
+        clock.set_wall_clock(clock.wall_clock() - Duration::from_mins(5));
+        assert_eq!(timer.remaining(), Duration::from_mins(50));
+
+        clock.set_wall_clock(clock.wall_clock() + Duration::from_hours(1));
+        assert_eq!(timer.remaining(), Duration::ZERO);
+        assert_eq!(timer.state(), TimerState::Expired);
+        assert!(timer.signed_remaining().is_negative());
+        assert_eq!(
+            timer.signed_remaining().magnitude(),
+            Duration::from_mins(10)
+        );
+
+        timer.set_deadline(Utc.with_ymd_and_hms(2024, 3, 2, 0, 0, 0).unwrap());
+        assert!(!timer.is_done());
+    }
+
+    #[test]
+    fn test_next_occurrence() {
+        let tz = FixedOffset::west_opt(5 * 3600).unwrap();
+        let time = NaiveTime::from_hms_opt(17, 30, 0).unwrap();
+
+        let morning = tz.with_ymd_and_hms(2024, 12, 31, 9, 0, 0).unwrap();
+        assert_eq!(
+            next_occurrence(time, &tz, morning.into()),
+            tz.with_ymd_and_hms(2024, 12, 31, 17, 30, 0).unwrap()
+        );
+
+        let on_time = tz.with_ymd_and_hms(2024, 12, 31, 17, 30, 0).unwrap();
+        assert_eq!(
+            next_occurrence(time, &tz, on_time.into()),
+            tz.with_ymd_and_hms(2025, 1, 1, 17, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_next_occurrence_across_dst() {
+        let tz = chrono_tz::Europe::Berlin;
+        let time = NaiveTime::from_hms_opt(2, 30, 0).unwrap();
+
+        // The clocks go from 02:00 straight to 03:00, so 02:30 is when they would have shown it.
+        let spring = Utc.with_ymd_and_hms(2024, 3, 30, 12, 0, 0).unwrap();
+        assert_eq!(
+            next_occurrence(time, &tz, spring.into()),
+            Utc.with_ymd_and_hms(2024, 3, 31, 1, 30, 0).unwrap()
+        );
+
+        // The clocks show 02:30 twice, and the first time counts.
+        let autumn = Utc.with_ymd_and_hms(2024, 10, 26, 12, 0, 0).unwrap();
+        assert_eq!(
+            next_occurrence(time, &tz, autumn.into()),
+            Utc.with_ymd_and_hms(2024, 10, 27, 0, 30, 0).unwrap()
+        );
+
+        // Between the two, the second time is still to come.
+        let between = Utc.with_ymd_and_hms(2024, 10, 27, 0, 45, 0).unwrap();
+        assert_eq!(
+            next_occurrence(time, &tz, between.into()),
+            Utc.with_ymd_and_hms(2024, 10, 27, 1, 30, 0).unwrap()
+        );
+    }
+}
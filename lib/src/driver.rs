@@ -0,0 +1,672 @@
+//! A module that drives a [`Timer`] from a background thread and reports what happens to it
+//!
+//! A [`Timer`] only changes when it is asked about, so noticing that it expired means polling it.
+//! A [`TimerDriver`] owns a timer and a background thread that sleeps until the timer is due to
+//! expire, so consumers can [`subscribe`](TimerDriver::subscribe) to [`TimerEvent`]s instead.
+//! Threads can also block until the timer expires with [`TimerDriver::wait`].
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, Weak};
+#[cfg(feature = "async")]
+use std::task::{Context, Poll, Waker};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::time_source::{SystemTimeSource, TimeSource};
+use crate::timer::{Timer, TimerState};
+
+/// Something that happened to a [`Timer`] owned by a [`TimerDriver`].
+///
+/// cbindgen:prefix-with-name
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimerEvent {
+    /// The timer was started for the first time since it was created or last reset.
+    Started,
+    /// The timer was stopped before it expired.
+    Paused,
+    /// The timer was started again after it was paused or given more time.
+    Resumed,
+    /// The timer was reset.
+    Reset,
+    /// The timer ran out of time. This is emitted once for every run that completes, including runs
+    /// that are repeated or reset by the [`CompletionPolicy`](crate::timer::CompletionPolicy).
+    Expired,
+    /// The timer reached the warning threshold at this index in [`Timer::warnings`].
+    Warning(usize),
+}
+
+/// How a call to [`TimerDriver::wait`] or [`TimerDriver::wait_timeout`] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WaitOutcome {
+    /// The timer ran out of time.
+    Expired,
+    /// The timer is paused.
+    Paused,
+    /// The timer is idle because it was reset or has never been started.
+    Idle,
+    /// The timer was changed in some other way, e.g. given more time, and is still running.
+    Modified,
+    /// The [`CancellationToken`] was cancelled.
+    Cancelled,
+    /// The timeout passed to [`TimerDriver::wait_timeout`] elapsed first.
+    TimedOut,
+}
+
+/// A token that makes the waits it is passed to return [`WaitOutcome::Cancelled`].
+///
+/// Clones share the same cancellation, so a clone can be handed to another thread to cancel a
+/// wait. Once cancelled, a token stays cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    /// The cancellation shared by all clones.
+    inner: Arc<Cancellation>,
+}
+
+/// The cancellation shared by all clones of a [`CancellationToken`].
+#[derive(Default)]
+struct Cancellation {
+    /// Whether the token was cancelled.
+    cancelled: AtomicBool,
+    /// The drivers that may have threads waiting with the token.
+    drivers: Mutex<Vec<Weak<dyn WakeWaiters>>>,
+}
+
+impl fmt::Debug for Cancellation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cancellation")
+            .field("cancelled", &self.cancelled)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Something threads can be blocked on in [`TimerDriver::wait`].
+trait WakeWaiters: Send + Sync {
+    /// Wakes every thread that is waiting.
+    fn wake_waiters(&self);
+}
+
+impl CancellationToken {
+    /// Creates a new [`CancellationToken`] that is not cancelled.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the token, waking the threads that are waiting with it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock has been poisoned.
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        let drivers = std::mem::take(&mut *self.inner.drivers.lock().unwrap());
+        for driver in drivers.iter().filter_map(Weak::upgrade) {
+            driver.wake_waiters();
+        }
+    }
+
+    /// Returns `true` if the token was cancelled.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Makes [`CancellationToken::cancel`] wake the threads waiting on `driver`.
+    fn register(&self, driver: Weak<dyn WakeWaiters>) {
+        let mut drivers = self.inner.drivers.lock().unwrap();
+        drivers.retain(|registered| registered.strong_count() > 0);
+        if !drivers.iter().any(|registered| registered.ptr_eq(&driver)) {
+            drivers.push(driver);
+        }
+    }
+}
+
+/// A [`Timer`] driven by a background thread that emits a [`TimerEvent`] whenever the timer
+/// changes.
+///
+/// All changes go through [`TimerDriver::update`], which is safe to call from any thread, so a
+/// driver is usually shared behind an [`Arc`]. The background thread stops when the driver is
+/// dropped.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use clock::driver::{TimerDriver, TimerEvent};
+/// use clock::timer::Timer;
+///
+/// let driver = TimerDriver::new(Timer::new(Duration::from_millis(10)));
+/// let events = driver.subscribe();
+///
+/// driver.update(Timer::start);
+///
+/// assert_eq!(events.recv(), Ok(TimerEvent::Started));
+/// assert_eq!(events.recv(), Ok(TimerEvent::Expired));
+/// ```
+#[derive(Debug)]
+pub struct TimerDriver<T: TimeSource + Send + 'static = SystemTimeSource> {
+    /// The state shared with the background thread.
+    shared: Arc<Shared<T>>,
+    /// The background thread, which is joined when the driver is dropped.
+    thread: Option<JoinHandle<()>>,
+}
+
+/// The state shared by a [`TimerDriver`] and its background thread.
+#[derive(Debug)]
+struct Shared<T: TimeSource> {
+    /// The timer and its subscribers.
+    inner: Mutex<Inner<T>>,
+    /// Notified whenever the timer changes or the driver is dropped.
+    changed: Condvar,
+}
+
+/// The state guarded by the lock of a [`TimerDriver`].
+#[derive(Debug)]
+struct Inner<T: TimeSource> {
+    /// The driven timer.
+    timer: Timer<T>,
+    /// The number of completions that have already been reported as [`TimerEvent::Expired`].
+    reported_completions: u32,
+    /// The channels events are sent to. Channels whose receiver was dropped are removed.
+    subscribers: Vec<Sender<TimerEvent>>,
+    /// The tasks waiting for the timer to expire, along with the id of the future that waits.
+    #[cfg(feature = "async")]
+    wakers: Vec<(u64, Waker)>,
+    /// The id given to the next future that waits for the timer to expire.
+    #[cfg(feature = "async")]
+    next_waiter: u64,
+    /// The number of times the timer was updated, used to notice changes while waiting.
+    updates: u64,
+    /// Whether the background thread should stop.
+    shutdown: bool,
+}
+
+impl<T: TimeSource + Send> WakeWaiters for Shared<T> {
+    fn wake_waiters(&self) {
+        // Taking the lock makes sure a waiting thread either sees the change or is already waiting.
+        drop(self.inner.lock());
+        self.changed.notify_all();
+    }
+}
+
+impl<T: TimeSource> Inner<T> {
+    /// Sends `event` to every subscriber, forgetting the ones that went away.
+    fn emit(&mut self, event: TimerEvent) {
+        self.subscribers
+            .retain(|subscriber| subscriber.send(event).is_ok());
+    }
+
+    /// Emits [`TimerEvent::Expired`] for every completion of the timer that was not reported yet.
+    fn emit_expiries(&mut self) {
+        let completions = self.timer.completions();
+        for _ in self.reported_completions..completions {
+            self.emit(TimerEvent::Expired);
+        }
+        #[cfg(feature = "async")]
+        if completions > self.reported_completions {
+            self.wakers.drain(..).for_each(|(_, waker)| waker.wake());
+        }
+        self.reported_completions = completions;
+    }
+
+    /// Emits [`TimerEvent::Warning`] for every threshold the timer reached since it was last
+    /// reported.
+    fn emit_warnings(&mut self) {
+        for index in self.timer.take_warnings() {
+            self.emit(TimerEvent::Warning(index));
+        }
+    }
+
+    /// Emits the event for the timer going from `before` to `after`, if there is one.
+    fn emit_transition(&mut self, before: TimerState, after: TimerState) {
+        let event = match (before, after) {
+            (TimerState::Idle, TimerState::Running) => TimerEvent::Started,
+            (TimerState::Paused | TimerState::Expired, TimerState::Running) => TimerEvent::Resumed,
+            (TimerState::Running, TimerState::Paused) => TimerEvent::Paused,
+            (TimerState::Running | TimerState::Paused | TimerState::Expired, TimerState::Idle) => {
+                TimerEvent::Reset
+            }
+            _ => return,
+        };
+        self.emit(event);
+    }
+}
+
+impl<T: TimeSource + Send + 'static> TimerDriver<T> {
+    /// Creates a new [`TimerDriver`] that owns `timer` and starts its background thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the background thread cannot be spawned.
+    #[must_use]
+    pub fn new(timer: Timer<T>) -> Self {
+        let shared = Arc::new(Shared {
+            inner: Mutex::new(Inner {
+                reported_completions: timer.completions(),
+                timer,
+                subscribers: Vec::new(),
+                #[cfg(feature = "async")]
+                wakers: Vec::new(),
+                #[cfg(feature = "async")]
+                next_waiter: 0,
+                updates: 0,
+                shutdown: false,
+            }),
+            changed: Condvar::new(),
+        });
+
+        let thread = {
+            let shared = Arc::clone(&shared);
+            thread::Builder::new()
+                .name("timer-driver".into())
+                .spawn(move || drive(&shared))
+                .expect("failed to spawn the timer driver thread")
+        };
+
+        Self {
+            shared,
+            thread: Some(thread),
+        }
+    }
+
+    /// Runs `f` on the timer and emits the events for whatever it changed.
+    ///
+    /// Any [`Timer`] method can be passed directly, e.g. `driver.update(Timer::start)` or
+    /// `driver.update(|timer| timer.add_time(Duration::from_secs(60)))`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock has been poisoned.
+    pub fn update<R>(&self, f: impl FnOnce(&mut Timer<T>) -> R) -> R {
+        let mut inner = self.lock();
+        inner.emit_warnings();
+        inner.emit_expiries();
+
+        let before = inner.timer.state();
+        let result = f(&mut inner.timer);
+        let after = inner.timer.state();
+        inner.emit_transition(before, after);
+        inner.emit_warnings();
+        inner.emit_expiries();
+        inner.updates += 1;
+
+        drop(inner);
+        self.shared.changed.notify_all();
+        result
+    }
+
+    /// Returns a copy of the timer as it is now.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock has been poisoned.
+    #[must_use]
+    pub fn timer(&self) -> Timer<T>
+    where
+        T: Clone,
+    {
+        self.lock().timer.clone()
+    }
+
+    /// Returns a [`Receiver`] for every event emitted from now on.
+    ///
+    /// The channel is closed once the driver is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock has been poisoned.
+    #[must_use]
+    pub fn subscribe(&self) -> Receiver<TimerEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.lock().subscribers.push(sender);
+        receiver
+    }
+
+    /// Calls `callback` for every event emitted from now on.
+    ///
+    /// The callback runs on a thread of its own, so it may call back into the driver. It stops
+    /// being called once the driver is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock has been poisoned or the callback thread cannot be spawned.
+    pub fn on_event(&self, mut callback: impl FnMut(TimerEvent) + Send + 'static) {
+        let events = self.subscribe();
+        thread::Builder::new()
+            .name("timer-events".into())
+            .spawn(move || events.iter().for_each(&mut callback))
+            .expect("failed to spawn the timer event thread");
+    }
+
+    /// Returns the number of runs the timer completed before the current one, which counts the
+    /// run it is expired in as not completed yet.
+    #[cfg(feature = "async")]
+    pub(crate) fn completions_before_current_run(&self) -> u32 {
+        let inner = self.lock();
+        let completions = inner.timer.completions();
+        if inner.timer.state() == TimerState::Expired {
+            completions.saturating_sub(1)
+        } else {
+            completions
+        }
+    }
+
+    /// Returns [`Poll::Ready`] once the timer has completed more than `completions` runs, and
+    /// otherwise wakes the task of `cx` when it expires.
+    ///
+    /// `waiter` identifies the waiting future. It is set on the first call, and is handed to
+    /// [`TimerDriver::forget_waker`] once the future no longer waits.
+    #[cfg(feature = "async")]
+    pub(crate) fn poll_completions(
+        &self,
+        completions: u32,
+        waiter: &mut Option<u64>,
+        cx: &mut Context<'_>,
+    ) -> Poll<()> {
+        let mut inner = self.lock();
+        if inner.timer.completions() > completions {
+            return Poll::Ready(());
+        }
+        let id = *waiter.get_or_insert_with(|| {
+            inner.next_waiter += 1;
+            inner.next_waiter
+        });
+        match inner.wakers.iter_mut().find(|(waiter, _)| *waiter == id) {
+            Some((_, waker)) => waker.clone_from(cx.waker()),
+            None => inner.wakers.push((id, cx.waker().clone())),
+        }
+        Poll::Pending
+    }
+
+    /// Drops the waker registered by the future identified by `waiter`, if it was not woken yet.
+    #[cfg(feature = "async")]
+    pub(crate) fn forget_waker(&self, waiter: u64) {
+        if let Ok(mut inner) = self.shared.inner.lock() {
+            inner.wakers.retain(|(id, _)| *id != waiter);
+        }
+    }
+
+    /// Blocks the current thread until the timer expires.
+    ///
+    /// The wait ends early when another thread pauses, resets or otherwise updates the timer, or
+    /// cancels `cancellation`, and the returned [`WaitOutcome`] says why. A timer that is not
+    /// running when this is called returns right away.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock has been poisoned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use clock::driver::{CancellationToken, TimerDriver, WaitOutcome};
+    /// use clock::timer::Timer;
+    ///
+    /// let driver = TimerDriver::new(Timer::new(Duration::from_millis(10)));
+    /// driver.update(Timer::start);
+    ///
+    /// assert_eq!(driver.wait(&CancellationToken::new()), WaitOutcome::Expired);
+    /// ```
+    #[must_use]
+    pub fn wait(&self, cancellation: &CancellationToken) -> WaitOutcome {
+        self.wait_until(None, cancellation)
+    }
+
+    /// Blocks the current thread like [`TimerDriver::wait`], for at most `timeout`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock has been poisoned.
+    #[must_use]
+    pub fn wait_timeout(&self, timeout: Duration, cancellation: &CancellationToken) -> WaitOutcome {
+        self.wait_until(Instant::now().checked_add(timeout), cancellation)
+    }
+
+    /// Blocks the current thread until the timer expires, changes, `cancellation` is cancelled
+    /// or `deadline` passes.
+    fn wait_until(
+        &self,
+        deadline: Option<Instant>,
+        cancellation: &CancellationToken,
+    ) -> WaitOutcome {
+        let shared: Arc<dyn WakeWaiters> = self.shared.clone();
+        cancellation.register(Arc::downgrade(&shared));
+
+        let mut inner = self.lock();
+        let completions = inner.timer.completions();
+        let updates = inner.updates;
+        loop {
+            if inner.timer.completions() > completions {
+                return WaitOutcome::Expired;
+            }
+            if cancellation.is_cancelled() {
+                return WaitOutcome::Cancelled;
+            }
+            match inner.timer.state() {
+                TimerState::Expired => return WaitOutcome::Expired,
+                TimerState::Paused => return WaitOutcome::Paused,
+                TimerState::Idle => return WaitOutcome::Idle,
+                TimerState::Running if inner.updates != updates => return WaitOutcome::Modified,
+                TimerState::Running => {}
+            }
+
+            let mut timeout = inner.timer.remaining();
+            if let Some(deadline) = deadline {
+                let now = Instant::now();
+                if now >= deadline {
+                    return WaitOutcome::TimedOut;
+                }
+                timeout = timeout.min(deadline.duration_since(now));
+            }
+            inner = self.shared.changed.wait_timeout(inner, timeout).unwrap().0;
+        }
+    }
+
+    /// Locks the state shared with the background thread.
+    fn lock(&self) -> MutexGuard<'_, Inner<T>> {
+        self.shared.inner.lock().unwrap()
+    }
+}
+
+impl<T: TimeSource + Send + 'static> Drop for TimerDriver<T> {
+    fn drop(&mut self) {
+        if let Ok(mut inner) = self.shared.inner.lock() {
+            inner.shutdown = true;
+        }
+        self.shared.changed.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// The loop of the background thread, which sleeps until the timer is due to warn or expire, or
+/// changes.
+fn drive<T: TimeSource>(shared: &Shared<T>) {
+    let Ok(mut inner) = shared.inner.lock() else {
+        return;
+    };
+    while !inner.shutdown {
+        inner.emit_warnings();
+        inner.emit_expiries();
+
+        let mut next_change = inner.timer.remaining();
+        if let Some(next_warning) = inner.timer.next_warning() {
+            next_change = next_change.min(next_warning);
+        }
+        inner = match inner.timer.state() {
+            TimerState::Running => match shared.changed.wait_timeout(inner, next_change) {
+                Ok((inner, _)) => inner,
+                Err(_) => return,
+            },
+            _ => match shared.changed.wait(inner) {
+                Ok(inner) => inner,
+                Err(_) => return,
+            },
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CancellationToken, TimerDriver, TimerEvent, WaitOutcome};
+    use crate::time_source::MockTimeSource;
+    use crate::timer::{CompletionPolicy, Timer, WarningThreshold};
+
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc::{self, Receiver};
+    use std::thread;
+    use std::time::Duration;
+
+    fn mock_driver(duration: Duration) -> (TimerDriver<MockTimeSource>, MockTimeSource) {
+        let clock = MockTimeSource::new();
+        let timer = Timer::with_time_source(duration, clock.clone());
+        (TimerDriver::new(timer), clock)
+    }
+
+    fn pending(events: &Receiver<TimerEvent>) -> Vec<TimerEvent> {
+        events.try_iter().collect()
+    }
+
+    #[test]
+    fn test_transitions() {
+        let (driver, clock) = mock_driver(Duration::from_secs(10));
+        let events = driver.subscribe();
+
+        driver.update(Timer::start);
+        driver.update(Timer::start);
+        clock.advance(Duration::from_secs(2));
+        driver.update(Timer::stop);
+        driver.update(Timer::start);
+        driver.update(Timer::reset);
+        driver.update(Timer::reset);
+
+        assert_eq!(
+            pending(&events),
+            vec![
+                TimerEvent::Started,
+                TimerEvent::Paused,
+                TimerEvent::Resumed,
+                TimerEvent::Reset
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expiry_is_reported_once_per_run() {
+        let (driver, clock) = mock_driver(Duration::from_secs(10));
+        driver.update(|timer| timer.set_completion_policy(CompletionPolicy::Repeat(1)));
+        let events = driver.subscribe();
+
+        driver.update(Timer::start);
+        clock.advance(Duration::from_secs(25));
+        driver.update(|_| ());
+        driver.update(|_| ());
+        driver.update(Timer::reset);
+
+        assert_eq!(
+            pending(&events),
+            vec![
+                TimerEvent::Started,
+                TimerEvent::Expired,
+                TimerEvent::Expired,
+                TimerEvent::Reset
+            ]
+        );
+    }
+
+    #[test]
+    fn test_driver_thread_reports_expiry() {
+        let driver = TimerDriver::new(Timer::new(Duration::from_millis(20)));
+        let (sender, events) = mpsc::channel();
+        driver.on_event(move |event| sender.send(event).unwrap());
+
+        driver.update(Timer::start);
+
+        let timeout = Duration::from_secs(5);
+        assert_eq!(events.recv_timeout(timeout), Ok(TimerEvent::Started));
+        assert_eq!(events.recv_timeout(timeout), Ok(TimerEvent::Expired));
+
+        drop(driver);
+        assert!(events.recv_timeout(timeout).is_err());
+    }
+
+    #[test]
+    fn test_wait_returns_on_changes() {
+        let (driver, _clock) = mock_driver(Duration::from_hours(1));
+        let token = CancellationToken::new();
+        assert_eq!(driver.wait(&token), WaitOutcome::Idle);
+
+        driver.update(Timer::start);
+        let waited = AtomicBool::new(false);
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                while !waited.load(Ordering::SeqCst) {
+                    driver.update(|timer| timer.add_time(Duration::from_secs(1)));
+                    thread::sleep(Duration::from_millis(1));
+                }
+            });
+            assert_eq!(driver.wait(&token), WaitOutcome::Modified);
+            waited.store(true, Ordering::SeqCst);
+        });
+
+        thread::scope(|scope| {
+            scope.spawn(|| driver.update(Timer::stop));
+            assert_eq!(driver.wait(&token), WaitOutcome::Paused);
+        });
+    }
+
+    #[test]
+    fn test_wait_cancellation_and_timeout() {
+        let (driver, _clock) = mock_driver(Duration::from_hours(1));
+        driver.update(Timer::start);
+
+        let token = CancellationToken::new();
+        assert_eq!(
+            driver.wait_timeout(Duration::from_millis(10), &token),
+            WaitOutcome::TimedOut
+        );
+
+        thread::scope(|scope| {
+            let canceller = token.clone();
+            scope.spawn(move || canceller.cancel());
+            assert_eq!(driver.wait(&token), WaitOutcome::Cancelled);
+        });
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn test_wait_until_expiry() {
+        let driver = TimerDriver::new(Timer::new(Duration::from_millis(20)));
+        driver.update(|timer| timer.set_completion_policy(CompletionPolicy::AutoReset));
+        driver.update(Timer::start);
+
+        assert_eq!(driver.wait(&CancellationToken::new()), WaitOutcome::Expired);
+    }
+
+    #[test]
+    fn test_warnings_are_emitted() {
+        let (driver, clock) = mock_driver(Duration::from_mins(10));
+        driver.update(|timer| timer.add_warning(WarningThreshold::PercentRemaining(50)));
+        let events = driver.subscribe();
+
+        driver.update(Timer::start);
+        clock.advance(Duration::from_mins(6));
+        driver.update(Timer::stop);
+        driver.update(Timer::start);
+
+        assert_eq!(
+            pending(&events),
+            vec![
+                TimerEvent::Started,
+                TimerEvent::Warning(0),
+                TimerEvent::Paused,
+                TimerEvent::Resumed
+            ]
+        );
+    }
+}
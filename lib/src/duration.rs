@@ -0,0 +1,196 @@
+//! A module that defines a signed counterpart of [`Duration`]
+
+use std::cmp::Ordering;
+use std::ops::{Add, Neg, Sub};
+use std::time::Duration;
+
+/// A span of time that can be negative, such as the time left on an overdue timer.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use clock::duration::SignedDuration;
+///
+/// let overdue = SignedDuration::difference(Duration::from_secs(60), Duration::from_secs(102));
+///
+/// assert!(overdue.is_negative());
+/// assert_eq!(overdue.magnitude(), Duration::from_secs(42));
+/// assert_eq!(-overdue, SignedDuration::positive(Duration::from_secs(42)));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SignedDuration {
+    /// The absolute length of the span.
+    magnitude: Duration,
+    /// Whether the span is negative. Always `false` for a zero span.
+    negative: bool,
+}
+
+impl SignedDuration {
+    /// A zero-length span.
+    pub const ZERO: SignedDuration = SignedDuration {
+        magnitude: Duration::ZERO,
+        negative: false,
+    };
+
+    /// Creates a non-negative [`SignedDuration`] of length `magnitude`.
+    #[must_use]
+    pub fn positive(magnitude: Duration) -> Self {
+        Self {
+            magnitude,
+            negative: false,
+        }
+    }
+
+    /// Creates a non-positive [`SignedDuration`] of length `magnitude`.
+    #[must_use]
+    pub fn negative(magnitude: Duration) -> Self {
+        Self {
+            magnitude,
+            negative: !magnitude.is_zero(),
+        }
+    }
+
+    /// Returns `lhs - rhs`, which is negative when `rhs` is longer than `lhs`.
+    #[must_use]
+    pub fn difference(lhs: Duration, rhs: Duration) -> Self {
+        if lhs >= rhs {
+            Self::positive(lhs.saturating_sub(rhs))
+        } else {
+            Self::negative(rhs.saturating_sub(lhs))
+        }
+    }
+
+    /// Returns the absolute length of the span.
+    #[must_use]
+    pub fn magnitude(&self) -> Duration {
+        self.magnitude
+    }
+
+    /// Returns `true` if the span is shorter than zero.
+    #[must_use]
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Returns `true` if the span is zero.
+    #[must_use]
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_zero()
+    }
+
+    /// Returns the span as a signed number of seconds.
+    #[must_use]
+    pub fn as_secs_f64(&self) -> f64 {
+        if self.negative {
+            -self.magnitude.as_secs_f64()
+        } else {
+            self.magnitude.as_secs_f64()
+        }
+    }
+}
+
+impl From<Duration> for SignedDuration {
+    fn from(duration: Duration) -> Self {
+        Self::positive(duration)
+    }
+}
+
+impl Neg for SignedDuration {
+    type Output = SignedDuration;
+
+    fn neg(self) -> Self::Output {
+        if self.negative {
+            Self::positive(self.magnitude)
+        } else {
+            Self::negative(self.magnitude)
+        }
+    }
+}
+
+impl Add for SignedDuration {
+    type Output = SignedDuration;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        match (self.negative, rhs.negative) {
+            (false, false) => Self::positive(self.magnitude + rhs.magnitude),
+            (true, true) => Self::negative(self.magnitude + rhs.magnitude),
+            (false, true) => Self::difference(self.magnitude, rhs.magnitude),
+            (true, false) => Self::difference(rhs.magnitude, self.magnitude),
+        }
+    }
+}
+
+impl Sub for SignedDuration {
+    type Output = SignedDuration;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl Ord for SignedDuration {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, false) => self.magnitude.cmp(&other.magnitude),
+            (true, true) => other.magnitude.cmp(&self.magnitude),
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+        }
+    }
+}
+
+impl PartialOrd for SignedDuration {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SignedDuration;
+
+    use std::time::Duration;
+
+    #[test]
+    fn test_arithmetic() {
+        let two = SignedDuration::positive(Duration::from_secs(2));
+        let minus_five = SignedDuration::negative(Duration::from_secs(5));
+
+        assert_eq!(
+            two + minus_five,
+            SignedDuration::negative(Duration::from_secs(3))
+        );
+        assert_eq!(
+            two - minus_five,
+            SignedDuration::positive(Duration::from_secs(7))
+        );
+        assert_eq!(minus_five - minus_five, SignedDuration::ZERO);
+        assert!(!(minus_five - minus_five).is_negative());
+    }
+
+    #[test]
+    fn test_ordering() {
+        let mut spans = vec![
+            SignedDuration::positive(Duration::from_secs(1)),
+            SignedDuration::negative(Duration::from_secs(1)),
+            SignedDuration::ZERO,
+            SignedDuration::negative(Duration::from_secs(3)),
+        ];
+        spans.sort();
+
+        assert_eq!(
+            spans,
+            vec![
+                SignedDuration::negative(Duration::from_secs(3)),
+                SignedDuration::negative(Duration::from_secs(1)),
+                SignedDuration::ZERO,
+                SignedDuration::positive(Duration::from_secs(1)),
+            ]
+        );
+        assert_eq!(
+            SignedDuration::negative(Duration::ZERO),
+            SignedDuration::ZERO
+        );
+    }
+}
@@ -0,0 +1,49 @@
+//! A module that defines the errors reported by timers and stopwatches
+
+use std::fmt;
+
+use crate::timer::TimerState;
+
+/// An error returned by the `try_*` methods of [`Timer`](crate::timer::Timer) and
+/// [`Stopwatch`](crate::stopwatch::Stopwatch).
+///
+/// The infallible counterparts of those methods silently ignore these conditions, while the `try_*`
+/// methods leave the clock untouched and report them so the caller can decide whether to recover,
+/// warn or reset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClockError {
+    /// The operation requires the clock to be stopped, but it is running.
+    AlreadyRunning,
+    /// The operation requires the clock to be running, but it is stopped.
+    NotRunning,
+    /// The time source reported a time earlier than one the clock had already recorded.
+    ClockWentBackwards,
+    /// A lap cannot end after the current elapsed time of a
+    /// [`Stopwatch`](crate::stopwatch::Stopwatch).
+    LapAfterElapsedTime,
+    /// A [`Timer`](crate::timer::Timer) cannot move between these states.
+    InvalidTransition {
+        /// The state the timer was in.
+        from: TimerState,
+        /// The state the timer was asked to move to.
+        to: TimerState,
+    },
+}
+
+impl fmt::Display for ClockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClockError::AlreadyRunning => f.write_str("the clock is already running"),
+            ClockError::NotRunning => f.write_str("the clock is not running"),
+            ClockError::ClockWentBackwards => f.write_str("the time source went backwards"),
+            ClockError::LapAfterElapsedTime => {
+                f.write_str("a lap cannot end after the elapsed time")
+            }
+            ClockError::InvalidTransition { from, to } => {
+                write!(f, "a timer cannot go from {from} to {to}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClockError {}
@@ -0,0 +1,473 @@
+//! A module that exports stopwatch sessions to CSV, JSON, Markdown and HTML
+//!
+//! A [`Session`] is a copy of everything a [`Stopwatch`] recorded: when it was started, the
+//! segments it ran and its laps. It can be written out in several formats for spreadsheets, notes
+//! or reports and, with the `json` feature, read back from JSON to be analysed again or to carry on
+//! with the stopwatch.
+
+use std::fmt::Write;
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, FixedOffset, Local, Offset, SecondsFormat};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::lap_statistics::LapStatistics;
+use crate::snapshot::StopwatchSnapshot;
+use crate::stopwatch::{format_time, LapTime, Segment, Stopwatch};
+use crate::time_source::TimeSource;
+
+/// Everything a [`Stopwatch`] recorded, taken with [`Session::from_stopwatch`].
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use clock::export::Session;
+/// use clock::stopwatch::Stopwatch;
+/// use clock::time_source::MockTimeSource;
+///
+/// let clock = MockTimeSource::new();
+/// let mut stopwatch = Stopwatch::with_time_source(clock.clone());
+/// stopwatch.start();
+/// clock.advance(Duration::from_millis(61_230));
+/// stopwatch.add_lap();
+///
+/// let session = Session::from_stopwatch(&stopwatch);
+/// let markdown = session.to_markdown();
+/// assert!(markdown.contains("| 1 | 01:01.23 | 01:01.23 |"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Session {
+    /// The time of day the stopwatch was first started.
+    started_at: Option<DateTime<FixedOffset>>,
+    /// The time of day the session was taken.
+    exported_at: DateTime<FixedOffset>,
+    /// Whether the stopwatch was running when the session was taken.
+    running: bool,
+    /// The elapsed time of the stopwatch.
+    elapsed: Duration,
+    /// The time the stopwatch spent stopped since it was first started.
+    paused: Duration,
+    /// The times the stopwatch ran, the last one up to when the session was taken if it was
+    /// running.
+    segments: Vec<Segment>,
+    /// The laps recorded by the stopwatch.
+    laps: Vec<LapTime>,
+}
+
+impl Session {
+    /// Takes a copy of everything `stopwatch` recorded so far.
+    #[must_use]
+    pub fn from_stopwatch<T: TimeSource>(stopwatch: &Stopwatch<T>) -> Self {
+        let exported_at = stopwatch.time_source().wall_clock();
+        Self {
+            started_at: stopwatch.started_at().map(local_time),
+            exported_at: local_time(exported_at),
+            running: stopwatch.is_running(),
+            elapsed: stopwatch.elapsed_time(),
+            paused: stopwatch.paused_time(),
+            segments: stopwatch.segments(),
+            laps: stopwatch.lap_times().to_vec(),
+        }
+    }
+
+    /// Returns the time of day the stopwatch was first started, or `None` if it was never started.
+    #[must_use]
+    pub fn started_at(&self) -> Option<DateTime<FixedOffset>> {
+        self.started_at
+    }
+
+    /// Returns the time of day the session was taken.
+    #[must_use]
+    pub fn exported_at(&self) -> DateTime<FixedOffset> {
+        self.exported_at
+    }
+
+    /// Returns whether the stopwatch was running when the session was taken.
+    #[must_use]
+    pub fn was_running(&self) -> bool {
+        self.running
+    }
+
+    /// Returns the elapsed time of the stopwatch.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns the time the stopwatch spent stopped since it was first started.
+    #[must_use]
+    pub fn paused(&self) -> Duration {
+        self.paused
+    }
+
+    /// Returns the times the stopwatch ran.
+    #[must_use]
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// Returns the laps recorded by the stopwatch.
+    #[must_use]
+    pub fn laps(&self) -> &[LapTime] {
+        &self.laps
+    }
+
+    /// Returns statistics over the split times of the laps.
+    #[must_use]
+    pub fn lap_statistics(&self) -> LapStatistics<'_> {
+        LapStatistics::new(&self.laps)
+    }
+
+    /// Returns a snapshot of a stopped stopwatch holding the segments and laps of the session, so
+    /// it can be reloaded with [`Stopwatch::restore`] and carried on with.
+    #[must_use]
+    pub fn to_snapshot(&self) -> StopwatchSnapshot {
+        let saved_at = SystemTime::from(self.exported_at);
+        StopwatchSnapshot {
+            running: false,
+            elapsed: self.elapsed,
+            started_at: self.started_at.map(SystemTime::from),
+            segments: self
+                .segments
+                .iter()
+                .map(|segment| segment.closed(saved_at))
+                .collect(),
+            lap_times: self.laps.clone(),
+            countdown: None,
+            paused: Some(self.paused),
+            saved_at,
+        }
+    }
+
+    /// Writes the session as CSV.
+    ///
+    /// The output has three sections separated by an empty line, each with its own header row:
+    /// the session metadata as key and value pairs, the segments and the laps. Durations are in
+    /// seconds and times of day in ISO 8601 format.
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        let mut row = |fields: &[&str]| {
+            let fields: Vec<_> = fields.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push_str("\r\n");
+        };
+
+        row(&["key", "value"]);
+        for (key, value) in self.metadata(seconds) {
+            row(&[key, &value]);
+        }
+        row(&[]);
+
+        row(&["segment", "started_at", "stopped_at", "duration"]);
+        for (index, segment) in self.segments.iter().enumerate() {
+            row(&[
+                &(index + 1).to_string(),
+                &iso8601(local_time(segment.started_at())),
+                &segment
+                    .stopped_at()
+                    .map(local_time)
+                    .map(iso8601)
+                    .unwrap_or_default(),
+                &seconds(segment.duration()),
+            ]);
+        }
+        row(&[]);
+
+        row(&[
+            "lap",
+            "split_time",
+            "total_time",
+            "recorded_at",
+            "label",
+            "note",
+        ]);
+        for lap in &self.laps {
+            row(&[
+                &lap.lap_number().to_string(),
+                &seconds(lap.split_time()),
+                &seconds(lap.total_time()),
+                &lap.recorded_at_iso8601().unwrap_or_default(),
+                lap.label().unwrap_or_default(),
+                lap.note().unwrap_or_default(),
+            ]);
+        }
+        csv
+    }
+
+    /// Writes the session as Markdown: a list of the session metadata followed by a table of the
+    /// segments and a table of the laps.
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::from("## Stopwatch session\n\n");
+        for (key, value) in self.metadata(format_time) {
+            let _ = writeln!(markdown, "- **{key}:** {}", markdown_cell(&value));
+        }
+
+        for Table {
+            title,
+            header,
+            rows,
+        } in self.tables()
+        {
+            let _ = write!(markdown, "\n### {title}\n\n| {} |\n|", header.join(" | "));
+            markdown.push_str(&" --- |".repeat(header.len()));
+            markdown.push('\n');
+            for row in rows {
+                let cells: Vec<_> = row.iter().map(|cell| markdown_cell(cell)).collect();
+                let _ = writeln!(markdown, "| {} |", cells.join(" | "));
+            }
+        }
+        markdown
+    }
+
+    /// Writes the session as an HTML fragment: a list of the session metadata followed by a table
+    /// of the segments and a table of the laps.
+    #[must_use]
+    pub fn to_html(&self) -> String {
+        let mut html = String::from("<section class=\"stopwatch-session\">\n<dl>\n");
+        for (key, value) in self.metadata(format_time) {
+            let _ = writeln!(
+                html,
+                "<dt>{}</dt><dd>{}</dd>",
+                html_text(key),
+                html_text(&value)
+            );
+        }
+        html.push_str("</dl>\n");
+
+        for Table {
+            title,
+            header,
+            rows,
+        } in self.tables()
+        {
+            let _ = writeln!(html, "<table>\n<caption>{}</caption>", html_text(title));
+            html.push_str("<thead><tr>");
+            for cell in header {
+                let _ = write!(html, "<th>{}</th>", html_text(cell));
+            }
+            html.push_str("</tr></thead>\n<tbody>\n");
+            for row in rows {
+                html.push_str("<tr>");
+                for cell in row {
+                    let _ = write!(html, "<td>{}</td>", html_text(&cell));
+                }
+                html.push_str("</tr>\n");
+            }
+            html.push_str("</tbody>\n</table>\n");
+        }
+        html.push_str("</section>\n");
+        html
+    }
+
+    /// Writes the session as JSON, which [`Session::from_json`] reads back.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session cannot be serialized, which does not happen in practice.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Reads a session written by [`Session::to_json`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `json` is not a session written by [`Session::to_json`].
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Returns the session metadata as key and value pairs, with durations written by `duration`.
+    fn metadata(&self, duration: impl Fn(Duration) -> String) -> Vec<(&'static str, String)> {
+        vec![
+            (
+                "started_at",
+                self.started_at.map(iso8601).unwrap_or_default(),
+            ),
+            ("exported_at", iso8601(self.exported_at)),
+            ("running", self.running.to_string()),
+            ("elapsed", duration(self.elapsed)),
+            ("paused", duration(self.paused)),
+            ("laps", self.laps.len().to_string()),
+        ]
+    }
+
+    /// Returns the segments and the laps as tables, for the formats meant to be read by people.
+    fn tables(&self) -> [Table; 2] {
+        let time_of_day =
+            |time: DateTime<FixedOffset>| time.format("%Y-%m-%d %H:%M:%S").to_string();
+        let segments = self
+            .segments
+            .iter()
+            .enumerate()
+            .map(|(index, segment)| {
+                vec![
+                    (index + 1).to_string(),
+                    time_of_day(local_time(segment.started_at())),
+                    segment
+                        .stopped_at()
+                        .map(local_time)
+                        .map(time_of_day)
+                        .unwrap_or_default(),
+                    format_time(segment.duration()),
+                ]
+            })
+            .collect();
+        let laps = self
+            .laps
+            .iter()
+            .map(|lap| {
+                vec![
+                    lap.lap_number().to_string(),
+                    format_time(lap.split_time()),
+                    format_time(lap.total_time()),
+                    lap.recorded_at().map(time_of_day).unwrap_or_default(),
+                    lap.label().unwrap_or_default().to_string(),
+                    lap.note().unwrap_or_default().to_string(),
+                ]
+            })
+            .collect();
+        [
+            Table {
+                title: "Segments",
+                header: &["Segment", "Started", "Stopped", "Duration"],
+                rows: segments,
+            },
+            Table {
+                title: "Laps",
+                header: &["Lap", "Split Time", "Total Time", "Time", "Label", "Note"],
+                rows: laps,
+            },
+        ]
+    }
+}
+
+/// A table of a [`Session`] in the formats meant to be read by people.
+struct Table {
+    /// The title shown above the table.
+    title: &'static str,
+    /// The names of the columns.
+    header: &'static [&'static str],
+    /// The cells of every row.
+    rows: Vec<Vec<String>>,
+}
+
+/// Returns `time` in the local time zone, keeping only its UTC offset.
+fn local_time(time: SystemTime) -> DateTime<FixedOffset> {
+    let time = DateTime::<Local>::from(time);
+    time.with_timezone(&time.offset().fix())
+}
+
+/// Writes `time` in ISO 8601 format.
+fn iso8601(time: DateTime<FixedOffset>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, false)
+}
+
+/// Writes `duration` as a number of seconds with millisecond precision.
+fn seconds(duration: Duration) -> String {
+    format!("{}.{:03}", duration.as_secs(), duration.subsec_millis())
+}
+
+/// Quotes `field` for CSV if it contains a separator, a quote or a line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Escapes `cell` so it stays within a Markdown table cell and is not read as HTML.
+fn markdown_cell(cell: &str) -> String {
+    cell.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('|', "\\|")
+        .replace(['\r', '\n'], " ")
+}
+
+/// Escapes `text` for use in HTML.
+fn html_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Session;
+    use crate::stopwatch::Stopwatch;
+    use crate::time_source::MockTimeSource;
+
+    use std::time::Duration;
+
+    fn session() -> Session {
+        let clock = MockTimeSource::new();
+        let mut stopwatch = Stopwatch::with_time_source(clock.clone());
+        stopwatch.start();
+        clock.advance(Duration::from_millis(1500));
+        stopwatch.add_lap();
+        stopwatch.stop();
+        clock.advance(Duration::from_secs(10));
+        stopwatch.start();
+        clock.advance(Duration::from_millis(2250));
+        stopwatch.add_lap();
+        let lap = stopwatch.lap_mut(2).unwrap();
+        lap.set_label(Some("Sprint, final".to_string()));
+        lap.set_note(Some("<fast> | \"ok\"".to_string()));
+        Session::from_stopwatch(&stopwatch)
+    }
+
+    #[test]
+    fn test_text_formats() {
+        let session = session();
+        assert_eq!(session.segments().len(), 2);
+        assert_eq!(session.paused(), Duration::from_secs(10));
+
+        let csv = session.to_csv();
+        let sections: Vec<_> = csv.split("\r\n\r\n").collect();
+        assert_eq!(sections.len(), 3);
+        assert!(sections[0].contains("\r\nelapsed,3.750\r\n"));
+        assert!(sections[1].starts_with("segment,started_at,stopped_at,duration\r\n"));
+        assert!(sections[2].contains(",1.500,1.500,"));
+        assert!(sections[2].contains(",\"Sprint, final\",\"<fast> | \"\"ok\"\"\"\r\n"));
+
+        let markdown = session.to_markdown();
+        assert!(markdown.contains("- **elapsed:** 00:03.75\n"));
+        assert!(markdown.contains("| Lap | Split Time | Total Time | Time | Label | Note |\n"));
+        assert!(markdown.contains("| Sprint, final | &lt;fast&gt; \\| \"ok\" |\n"));
+
+        let html = session.to_html();
+        assert!(html.contains("<td>Sprint, final</td><td>&lt;fast&gt; | &quot;ok&quot;</td>"));
+        assert_eq!(html.matches("<table>").count(), 2);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_round_trip() {
+        let session = session();
+        let json = session.to_json().unwrap();
+        let imported = Session::from_json(&json).unwrap();
+        assert_eq!(imported, session);
+        assert_eq!(imported.lap_statistics().fastest().unwrap().lap_number(), 1);
+        assert!(Session::from_json("{}").is_err());
+
+        let stopwatch = Stopwatch::restore(imported.to_snapshot());
+        assert!(!stopwatch.is_running());
+        assert_eq!(stopwatch.elapsed_time(), Duration::from_millis(3750));
+        assert_eq!(stopwatch.lap_times(), session.laps());
+        assert!(stopwatch
+            .segments()
+            .iter()
+            .all(|segment| !segment.is_open()));
+    }
+}
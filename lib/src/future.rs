@@ -0,0 +1,162 @@
+//! A module that provides futures for timers, enabled by the `async` feature
+//!
+//! The futures are woken by the background thread of a [`TimerDriver`], so they work with any
+//! async runtime and do not need one of their own.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::driver::TimerDriver;
+use crate::time_source::TimeSource;
+
+/// A future that resolves when the timer of a [`TimerDriver`] expires, see
+/// [`TimerDriver::expired`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Expired<'a, T: TimeSource + Send + 'static> {
+    /// The driver of the timer.
+    driver: &'a TimerDriver<T>,
+    /// The number of runs the timer had completed when the future was created.
+    completions: u32,
+    /// The id the waker of the future is registered under, once it was polled.
+    waiter: Option<u64>,
+}
+
+impl<T: TimeSource + Send + 'static> Future for Expired<'_, T> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        this.driver
+            .poll_completions(this.completions, &mut this.waiter, cx)
+    }
+}
+
+impl<T: TimeSource + Send + 'static> Drop for Expired<'_, T> {
+    /// Drops the waker of the future, so a timer that never expires does not keep the task alive.
+    fn drop(&mut self) {
+        if let Some(waiter) = self.waiter {
+            self.driver.forget_waker(waiter);
+        }
+    }
+}
+
+impl<T: TimeSource + Send + 'static> TimerDriver<T> {
+    /// Returns a future that resolves when the timer runs out of time.
+    ///
+    /// The future follows every change made to the timer after it was created: pausing the timer
+    /// delays it, and resetting the timer or changing its duration moves the expiry it waits for.
+    /// It resolves right away if the timer has already expired.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock has been poisoned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn run() {
+    /// use std::time::Duration;
+    /// use clock::driver::TimerDriver;
+    /// use clock::timer::Timer;
+    ///
+    /// let driver = TimerDriver::new(Timer::new(Duration::from_secs(60)));
+    /// driver.update(Timer::start);
+    ///
+    /// driver.expired().await;
+    /// # }
+    /// ```
+    pub fn expired(&self) -> Expired<'_, T> {
+        Expired {
+            driver: self,
+            completions: self.completions_before_current_run(),
+            waiter: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::driver::TimerDriver;
+    use crate::time_source::MockTimeSource;
+    use crate::timer::Timer;
+
+    use std::future::Future;
+    use std::pin::pin;
+    use std::sync::mpsc::{self, Sender};
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::time::Duration;
+
+    /// A waker that reports every wake-up on a channel.
+    struct ChannelWaker(Mutex<Sender<()>>);
+
+    impl Wake for ChannelWaker {
+        fn wake(self: Arc<Self>) {
+            let _ = self.0.lock().unwrap().send(());
+        }
+    }
+
+    #[test]
+    fn test_expired_follows_changes() {
+        let clock = MockTimeSource::new();
+        let driver = TimerDriver::new(Timer::with_time_source(
+            Duration::from_secs(10),
+            clock.clone(),
+        ));
+        let (sender, wake_ups) = mpsc::channel();
+        let waker = Waker::from(Arc::new(ChannelWaker(Mutex::new(sender))));
+        let mut cx = Context::from_waker(&waker);
+
+        let mut expired = pin!(driver.expired());
+        assert_eq!(expired.as_mut().poll(&mut cx), Poll::Pending);
+
+        driver.update(Timer::start);
+        clock.advance(Duration::from_secs(5));
+        driver.update(Timer::stop);
+        clock.advance(Duration::from_mins(1));
+        driver.update(|timer| timer.add_time(Duration::from_secs(5)));
+        assert_eq!(expired.as_mut().poll(&mut cx), Poll::Pending);
+
+        driver.update(Timer::start);
+        clock.advance(Duration::from_secs(10));
+        driver.update(|_| ());
+        assert!(wake_ups.try_recv().is_ok());
+        assert_eq!(expired.as_mut().poll(&mut cx), Poll::Ready(()));
+
+        let mut already_expired = pin!(driver.expired());
+        assert_eq!(already_expired.as_mut().poll(&mut cx), Poll::Ready(()));
+    }
+
+    #[test]
+    fn test_dropped_future_releases_waker() {
+        let driver = TimerDriver::new(Timer::new(Duration::from_mins(1)));
+        let (sender, _wake_ups) = mpsc::channel();
+        let channel_waker = Arc::new(ChannelWaker(Mutex::new(sender)));
+        let waker = Waker::from(Arc::clone(&channel_waker));
+        let mut cx = Context::from_waker(&waker);
+
+        {
+            let mut expired = pin!(driver.expired());
+            assert_eq!(expired.as_mut().poll(&mut cx), Poll::Pending);
+            assert_eq!(expired.as_mut().poll(&mut cx), Poll::Pending);
+            assert_eq!(Arc::strong_count(&channel_waker), 3);
+        }
+        assert_eq!(Arc::strong_count(&channel_waker), 2);
+    }
+
+    #[test]
+    fn test_driver_thread_wakes_future() {
+        let driver = TimerDriver::new(Timer::new(Duration::from_millis(20)));
+        let (sender, wake_ups) = mpsc::channel();
+        let waker = Waker::from(Arc::new(ChannelWaker(Mutex::new(sender))));
+        let mut cx = Context::from_waker(&waker);
+
+        let mut expired = pin!(driver.expired());
+        driver.update(Timer::start);
+        while expired.as_mut().poll(&mut cx).is_pending() {
+            wake_ups.recv_timeout(Duration::from_secs(5)).unwrap();
+        }
+    }
+}
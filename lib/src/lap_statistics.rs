@@ -0,0 +1,211 @@
+//! A module that computes statistics over the laps of a stopwatch
+//!
+//! Every statistic is computed from the split times of the laps, so front-ends can show the best
+//! and worst laps and how each lap compares to the others without redoing the arithmetic.
+
+use std::time::Duration;
+
+use crate::duration::SignedDuration;
+use crate::stopwatch::LapTime;
+
+/// Statistics over the split times of a list of laps, see
+/// [`Stopwatch::lap_statistics`](crate::stopwatch::Stopwatch::lap_statistics).
+///
+/// Every statistic is `None` when there are no laps.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use clock::lap_statistics::LapStatistics;
+/// use clock::stopwatch::LapTime;
+///
+/// let laps = [
+///     LapTime::new(1, Duration::from_secs(62), Duration::from_secs(62)),
+///     LapTime::new(2, Duration::from_secs(58), Duration::from_secs(120)),
+///     LapTime::new(3, Duration::from_secs(60), Duration::from_secs(180)),
+/// ];
+/// let statistics = LapStatistics::new(&laps);
+///
+/// assert_eq!(statistics.fastest().map(|lap| lap.lap_number()), Some(2));
+/// assert_eq!(statistics.slowest().map(|lap| lap.lap_number()), Some(1));
+/// assert_eq!(statistics.mean(), Some(Duration::from_secs(60)));
+/// assert_eq!(statistics.rolling_average(2), Some(Duration::from_secs(59)));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct LapStatistics<'a> {
+    /// The laps the statistics are computed over.
+    laps: &'a [LapTime],
+}
+
+impl<'a> LapStatistics<'a> {
+    /// Creates the statistics over `laps`.
+    #[must_use]
+    pub fn new(laps: &'a [LapTime]) -> Self {
+        Self { laps }
+    }
+
+    /// Returns the number of laps.
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.laps.len()
+    }
+
+    /// Returns the lap with the shortest split time, the earliest one if several are equally fast.
+    #[must_use]
+    pub fn fastest(&self) -> Option<&'a LapTime> {
+        self.laps.iter().reduce(|fastest, lap| {
+            if lap.split_time() < fastest.split_time() {
+                lap
+            } else {
+                fastest
+            }
+        })
+    }
+
+    /// Returns the lap with the longest split time, the earliest one if several are equally slow.
+    #[must_use]
+    pub fn slowest(&self) -> Option<&'a LapTime> {
+        self.laps.iter().reduce(|slowest, lap| {
+            if lap.split_time() > slowest.split_time() {
+                lap
+            } else {
+                slowest
+            }
+        })
+    }
+
+    /// Returns the mean split time.
+    #[must_use]
+    pub fn mean(&self) -> Option<Duration> {
+        mean(self.laps)
+    }
+
+    /// Returns the median split time. With an even number of laps this is the mean of the two
+    /// middle split times.
+    #[must_use]
+    pub fn median(&self) -> Option<Duration> {
+        let mut splits: Vec<_> = self.laps.iter().map(LapTime::split_time).collect();
+        splits.sort_unstable();
+        let middle = splits.len() / 2;
+        match splits.len() {
+            0 => None,
+            len if len % 2 == 1 => Some(splits[middle]),
+            _ => Some((splits[middle - 1] + splits[middle]) / 2),
+        }
+    }
+
+    /// Returns the standard deviation of the split times, treating the laps as the whole
+    /// population rather than a sample of it.
+    #[must_use]
+    pub fn standard_deviation(&self) -> Option<Duration> {
+        let mean = self.mean()?.as_secs_f64();
+        #[allow(clippy::cast_precision_loss)]
+        let variance = self
+            .laps
+            .iter()
+            .map(|lap| (lap.split_time().as_secs_f64() - mean).powi(2))
+            .sum::<f64>()
+            / self.laps.len() as f64;
+        Some(Duration::from_secs_f64(variance.sqrt()))
+    }
+
+    /// Returns the mean split time of the last `count` laps, or of all laps if there are fewer.
+    /// Returns `None` if `count` is zero.
+    #[must_use]
+    pub fn rolling_average(&self, count: usize) -> Option<Duration> {
+        mean(&self.laps[self.laps.len().saturating_sub(count)..])
+    }
+
+    /// Returns how much longer or shorter than the mean split time each lap took, in lap order.
+    /// Laps faster than the mean have a negative delta.
+    #[must_use]
+    pub fn deltas_from_mean(&self) -> Vec<SignedDuration> {
+        let Some(mean) = self.mean() else {
+            return Vec::new();
+        };
+        self.laps
+            .iter()
+            .map(|lap| SignedDuration::difference(lap.split_time(), mean))
+            .collect()
+    }
+}
+
+/// Returns the mean split time of `laps`, or `None` if there are none.
+fn mean(laps: &[LapTime]) -> Option<Duration> {
+    if laps.is_empty() {
+        return None;
+    }
+    let total: u128 = laps.iter().map(|lap| lap.split_time().as_nanos()).sum();
+    let mean = total / laps.len() as u128;
+    Some(Duration::from_nanos(
+        u64::try_from(mean).unwrap_or(u64::MAX),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LapStatistics;
+    use crate::duration::SignedDuration;
+    use crate::stopwatch::LapTime;
+
+    use std::time::Duration;
+
+    fn laps(splits: &[u64]) -> Vec<LapTime> {
+        let mut total = Duration::ZERO;
+        splits
+            .iter()
+            .enumerate()
+            .map(|(index, &split)| {
+                total += Duration::from_secs(split);
+                LapTime::new(index + 1, Duration::from_secs(split), total)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_no_laps() {
+        let statistics = LapStatistics::new(&[]);
+        assert_eq!(statistics.count(), 0);
+        assert!(statistics.fastest().is_none());
+        assert!(statistics.slowest().is_none());
+        assert_eq!(statistics.mean(), None);
+        assert_eq!(statistics.median(), None);
+        assert_eq!(statistics.standard_deviation(), None);
+        assert_eq!(statistics.rolling_average(3), None);
+        assert!(statistics.deltas_from_mean().is_empty());
+    }
+
+    #[test]
+    fn test_statistics() {
+        let laps = laps(&[2, 4, 4, 4, 5, 5, 7, 9]);
+        let statistics = LapStatistics::new(&laps);
+
+        assert_eq!(statistics.fastest().unwrap().lap_number(), 1);
+        assert_eq!(statistics.slowest().unwrap().lap_number(), 8);
+        assert_eq!(statistics.mean(), Some(Duration::from_secs(5)));
+        assert_eq!(statistics.median(), Some(Duration::from_millis(4500)));
+        assert_eq!(
+            statistics.standard_deviation(),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(statistics.rolling_average(3), Some(Duration::from_secs(7)));
+        assert_eq!(statistics.rolling_average(20), statistics.mean());
+        assert_eq!(statistics.rolling_average(0), None);
+
+        let deltas = statistics.deltas_from_mean();
+        assert_eq!(deltas.len(), 8);
+        assert_eq!(deltas[0], SignedDuration::negative(Duration::from_secs(3)));
+        assert_eq!(deltas[4], SignedDuration::ZERO);
+        assert_eq!(deltas[7], SignedDuration::positive(Duration::from_secs(4)));
+    }
+
+    #[test]
+    fn test_ties_pick_earliest_lap() {
+        let laps = laps(&[3, 1, 3, 1]);
+        let statistics = LapStatistics::new(&laps);
+        assert_eq!(statistics.fastest().unwrap().lap_number(), 2);
+        assert_eq!(statistics.slowest().unwrap().lap_number(), 1);
+        assert_eq!(statistics.median(), Some(Duration::from_secs(2)));
+    }
+}
@@ -2,8 +2,23 @@
 
 #![warn(missing_docs, clippy::pedantic, clippy::cargo, clippy::all)]
 
+pub mod comparison;
+pub mod deadline;
+pub mod driver;
+pub mod duration;
+pub mod error;
+pub mod export;
+#[cfg(feature = "async")]
+pub mod future;
+pub mod lap_statistics;
+pub mod pomodoro;
+pub mod race;
+pub mod sequence;
+pub mod snapshot;
 pub mod stopwatch;
+pub mod time_source;
 pub mod timer;
+pub mod timer_set;
 
 #[cfg(target_os = "macos")]
 pub mod macos;
@@ -2,6 +2,8 @@
 
 #![allow(non_snake_case)]
 
+mod driver;
 mod rust;
 mod stopwatch;
 mod timer;
+mod timer_set;
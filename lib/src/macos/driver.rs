@@ -0,0 +1,195 @@
+use std::{ffi::c_void, time::Duration};
+
+use crate::driver::{TimerDriver, TimerEvent};
+use crate::timer::{CompletionPolicy, Timer, TimerState, WarningThreshold};
+
+/// The context pointer handed back to an event callback.
+struct CallbackContext(*mut c_void);
+
+// SAFETY: the caller of `driver_TimerDriver_onEvent` promises that the context may be used from
+// the thread the callback runs on.
+unsafe impl Send for CallbackContext {}
+
+impl CallbackContext {
+    fn get(&self) -> *mut c_void {
+        self.0
+    }
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub extern "C" fn driver_TimerDriver_create(secs: u64) -> *mut c_void {
+    let driver = TimerDriver::new(Timer::new(Duration::from_secs(secs)));
+    Box::into_raw(Box::new(driver)).cast::<std::ffi::c_void>()
+}
+
+/// Creates a driver that takes over a timer, e.g. one restored with `timer_Timer_restoreJson`.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer and takes ownership of the timer, which must not be
+/// used or freed afterwards
+#[no_mangle]
+pub unsafe extern "C" fn driver_TimerDriver_fromTimer(timer: *mut c_void) -> *mut c_void {
+    let timer = *Box::from_raw(timer.cast::<Timer>());
+    Box::into_raw(Box::new(TimerDriver::new(timer))).cast::<std::ffi::c_void>()
+}
+
+/// Returns a copy of the timer as it is now, freed with `timer_Timer_free`.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn driver_TimerDriver_timer(driver: *mut c_void) -> *mut c_void {
+    let timer = (*driver.cast::<TimerDriver>()).timer();
+    Box::into_raw(Box::new(timer)).cast::<std::ffi::c_void>()
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer and frees the object
+#[no_mangle]
+pub unsafe extern "C" fn driver_TimerDriver_free(driver: *mut c_void) {
+    drop(Box::from_raw(driver.cast::<TimerDriver>()));
+}
+
+/// Calls `callback` with `context` for every event of the driver, on a background thread.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer, and `context` must be usable from any thread until
+/// the driver is freed
+#[no_mangle]
+pub unsafe extern "C" fn driver_TimerDriver_onEvent(
+    driver: *mut c_void,
+    callback: extern "C" fn(TimerEvent, *mut c_void),
+    context: *mut c_void,
+) {
+    let context = CallbackContext(context);
+    (*driver.cast::<TimerDriver>()).on_event(move |event| callback(event, context.get()));
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn driver_TimerDriver_start(driver: *mut c_void) {
+    (*driver.cast::<TimerDriver>()).update(Timer::start);
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn driver_TimerDriver_stop(driver: *mut c_void) {
+    (*driver.cast::<TimerDriver>()).update(Timer::stop);
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn driver_TimerDriver_reset(driver: *mut c_void) {
+    (*driver.cast::<TimerDriver>()).update(Timer::reset);
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn driver_TimerDriver_setCompletionPolicy(
+    driver: *mut c_void,
+    policy: CompletionPolicy,
+) {
+    (*driver.cast::<TimerDriver>()).update(|timer| timer.set_completion_policy(policy));
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn driver_TimerDriver_setOvertime(driver: *mut c_void, enabled: bool) {
+    (*driver.cast::<TimerDriver>()).update(|timer| timer.set_overtime(enabled));
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn driver_TimerDriver_setDuration(driver: *mut c_void, secs: u64) {
+    (*driver.cast::<TimerDriver>()).update(|timer| timer.set_duration(Duration::from_secs(secs)));
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn driver_TimerDriver_setRemaining(driver: *mut c_void, secs: u64) {
+    (*driver.cast::<TimerDriver>()).update(|timer| timer.set_remaining(Duration::from_secs(secs)));
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn driver_TimerDriver_addTime(driver: *mut c_void, secs: u64) {
+    (*driver.cast::<TimerDriver>()).update(|timer| timer.add_time(Duration::from_secs(secs)));
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn driver_TimerDriver_subtractTime(driver: *mut c_void, secs: u64) {
+    (*driver.cast::<TimerDriver>()).update(|timer| timer.subtract_time(Duration::from_secs(secs)));
+}
+
+/// Makes the driver emit `TimerEvent_Warning` once `secs` seconds remain.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn driver_TimerDriver_addWarningSecs(driver: *mut c_void, secs: u64) {
+    (*driver.cast::<TimerDriver>())
+        .update(|timer| timer.add_warning(WarningThreshold::Remaining(Duration::from_secs(secs))));
+}
+
+/// Makes the driver emit `TimerEvent_Warning` once `percent` percent of the duration remains.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn driver_TimerDriver_addWarningPercent(driver: *mut c_void, percent: u8) {
+    (*driver.cast::<TimerDriver>())
+        .update(|timer| timer.add_warning(WarningThreshold::PercentRemaining(percent)));
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn driver_TimerDriver_clearWarnings(driver: *mut c_void) {
+    (*driver.cast::<TimerDriver>()).update(Timer::clear_warnings);
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn driver_TimerDriver_remaining(driver: *mut c_void) -> *mut c_void {
+    let duration = (*driver.cast::<TimerDriver>()).timer().remaining();
+    Box::into_raw(Box::new(duration)).cast::<std::ffi::c_void>()
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn driver_TimerDriver_state(driver: *mut c_void) -> TimerState {
+    (*driver.cast::<TimerDriver>()).timer().state()
+}
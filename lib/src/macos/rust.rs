@@ -44,3 +44,11 @@ pub unsafe extern "C" fn rust_Duration_eq(duration: *const c_void, other: *const
     let other_ptr = other.cast::<std::time::Duration>();
     (*duration_ptr).eq(&*other_ptr)
 }
+
+/// # Safety
+///
+/// This function dereferences a raw pointer and frees the string
+#[no_mangle]
+pub unsafe extern "C" fn rust_String_free(string: *mut std::ffi::c_char) {
+    drop(std::ffi::CString::from_raw(string));
+}
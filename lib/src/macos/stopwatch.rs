@@ -1,5 +1,10 @@
-use std::ffi::{c_char, c_void, CString};
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::time::Duration;
 
+use crate::duration::SignedDuration;
+use crate::export::Session;
+#[cfg(feature = "json")]
+use crate::snapshot::StopwatchSnapshot;
 use crate::stopwatch::{self, format_time, LapTime, Stopwatch};
 
 /// A buffer that stores `LapTime` values for a stopwatch.
@@ -33,6 +38,40 @@ pub unsafe extern "C" fn stopwatch_Stopwatch_start(stopwatch: *mut c_void) {
     (*stopwatch.cast::<Stopwatch>()).start();
 }
 
+/// Starts the stopwatch once `secs` seconds have passed, with its zero at the end of the countdown.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_Stopwatch_startAfter(stopwatch: *mut c_void, secs: f64) {
+    if let Ok(countdown) = Duration::try_from_secs_f64(secs) {
+        (*stopwatch.cast::<Stopwatch>()).start_after(countdown);
+    }
+}
+
+/// Returns the time left until a delayed start, or null if the stopwatch is not counting down.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_Stopwatch_countdownRemaining(
+    stopwatch: *mut c_void,
+) -> *mut c_void {
+    into_raw_duration((*stopwatch.cast::<Stopwatch>()).countdown_remaining())
+}
+
+/// Cancels a delayed start. Returns false if the stopwatch was not counting down.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_Stopwatch_cancelCountdown(stopwatch: *mut c_void) -> bool {
+    (*stopwatch.cast::<Stopwatch>()).cancel_countdown()
+}
+
 /// # Safety
 ///
 /// This function dereferences a raw pointer
@@ -75,6 +114,40 @@ pub unsafe extern "C" fn stopwatch_Stopwatch_elapsedTime(stopwatch: *mut c_void)
     Box::into_raw(Box::new(duration)).cast::<std::ffi::c_void>()
 }
 
+/// Returns the wall-clock time the stopwatch spent stopped since it was first started.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_Stopwatch_pausedTime(stopwatch: *mut c_void) -> *mut c_void {
+    let duration = (*stopwatch.cast::<Stopwatch>()).paused_time();
+
+    Box::into_raw(Box::new(duration)).cast::<std::ffi::c_void>()
+}
+
+/// Exports the stopwatch session, with its segments and laps, as CSV.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_Stopwatch_exportCsv(stopwatch: *mut c_void) -> *mut c_char {
+    let session = Session::from_stopwatch(&*stopwatch.cast::<Stopwatch>());
+    into_raw_string(Some(&session.to_csv()))
+}
+
+/// Exports the stopwatch session, with its segments and laps, as Markdown tables.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_Stopwatch_exportMarkdown(stopwatch: *mut c_void) -> *mut c_char {
+    let session = Session::from_stopwatch(&*stopwatch.cast::<Stopwatch>());
+    into_raw_string(Some(&session.to_markdown()))
+}
+
 /// # Safety
 ///
 /// This function dereferences a raw pointer
@@ -86,6 +159,245 @@ pub unsafe extern "C" fn stopwatch_Stopwatch_lapTimes(stopwatch: *mut c_void) ->
     LapTimeBuffer { data, len }
 }
 
+/// Returns the number of the fastest lap, or 0 if there are no laps.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_Stopwatch_fastestLap(stopwatch: *mut c_void) -> usize {
+    (*stopwatch.cast::<Stopwatch>())
+        .lap_statistics()
+        .fastest()
+        .map_or(0, LapTime::lap_number)
+}
+
+/// Returns the number of the slowest lap, or 0 if there are no laps.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_Stopwatch_slowestLap(stopwatch: *mut c_void) -> usize {
+    (*stopwatch.cast::<Stopwatch>())
+        .lap_statistics()
+        .slowest()
+        .map_or(0, LapTime::lap_number)
+}
+
+/// Returns the mean split time of the laps, or null if there are no laps.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_Stopwatch_lapMean(stopwatch: *mut c_void) -> *mut c_void {
+    into_raw_duration((*stopwatch.cast::<Stopwatch>()).lap_statistics().mean())
+}
+
+/// Returns the median split time of the laps, or null if there are no laps.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_Stopwatch_lapMedian(stopwatch: *mut c_void) -> *mut c_void {
+    into_raw_duration((*stopwatch.cast::<Stopwatch>()).lap_statistics().median())
+}
+
+/// Returns the standard deviation of the split times of the laps, or null if there are no laps.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_Stopwatch_lapStandardDeviation(
+    stopwatch: *mut c_void,
+) -> *mut c_void {
+    into_raw_duration(
+        (*stopwatch.cast::<Stopwatch>())
+            .lap_statistics()
+            .standard_deviation(),
+    )
+}
+
+/// Returns the mean split time of the last `count` laps, or null if there are no laps or `count`
+/// is 0.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_Stopwatch_lapRollingAverage(
+    stopwatch: *mut c_void,
+    count: usize,
+) -> *mut c_void {
+    into_raw_duration(
+        (*stopwatch.cast::<Stopwatch>())
+            .lap_statistics()
+            .rolling_average(count),
+    )
+}
+
+/// Returns how many seconds longer than the mean split time the lap numbered `lap_number` took,
+/// which is negative for laps faster than the mean and 0 if there is no such lap.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_Stopwatch_lapDeltaFromMean(
+    stopwatch: *mut c_void,
+    lap_number: usize,
+) -> f64 {
+    let Some(index) = lap_number.checked_sub(1) else {
+        return 0.0;
+    };
+    (*stopwatch.cast::<Stopwatch>())
+        .lap_statistics()
+        .deltas_from_mean()
+        .get(index)
+        .map_or(0.0, SignedDuration::as_secs_f64)
+}
+
+/// Removes a lap and merges its time into the lap that follows it. Returns false if there is no
+/// such lap.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_Stopwatch_removeLap(
+    stopwatch: *mut c_void,
+    lap_number: usize,
+) -> bool {
+    (*stopwatch.cast::<Stopwatch>())
+        .remove_lap(lap_number)
+        .is_some()
+}
+
+/// Inserts a lap that ended `secs` seconds after the start of the stopwatch. Returns false if that
+/// is past the elapsed time of the stopwatch.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_Stopwatch_insertLap(stopwatch: *mut c_void, secs: f64) -> bool {
+    Duration::try_from_secs_f64(secs).is_ok_and(|total_time| {
+        (*stopwatch.cast::<Stopwatch>())
+            .insert_lap(total_time)
+            .is_ok()
+    })
+}
+
+/// Sets the label of a lap, or removes it if `label` is null. Returns false if there is no such
+/// lap.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer, and `label` must be null or a valid C string
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_Stopwatch_setLapLabel(
+    stopwatch: *mut c_void,
+    lap_number: usize,
+    label: *const c_char,
+) -> bool {
+    match (*stopwatch.cast::<Stopwatch>()).lap_mut(lap_number) {
+        Some(lap) => {
+            lap.set_label(from_raw_string(label));
+            true
+        }
+        None => false,
+    }
+}
+
+/// Sets the note of a lap, or removes it if `note` is null. Returns false if there is no such lap.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer, and `note` must be null or a valid C string
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_Stopwatch_setLapNote(
+    stopwatch: *mut c_void,
+    lap_number: usize,
+    note: *const c_char,
+) -> bool {
+    match (*stopwatch.cast::<Stopwatch>()).lap_mut(lap_number) {
+        Some(lap) => {
+            lap.set_note(from_raw_string(note));
+            true
+        }
+        None => false,
+    }
+}
+
+/// Returns a snapshot of the stopwatch, with its laps, as JSON, to be restored with
+/// `stopwatch_Stopwatch_restoreJson` after the app restarts. The string is freed with
+/// `rust_String_free`, and is null if the snapshot could not be serialized.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[cfg(feature = "json")]
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_Stopwatch_snapshotJson(stopwatch: *mut c_void) -> *mut c_char {
+    match serde_json::to_string(&(*stopwatch.cast::<Stopwatch>()).snapshot()) {
+        Ok(json) => into_raw_string(Some(&json)),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Creates a stopwatch from a snapshot returned by `stopwatch_Stopwatch_snapshotJson`, or returns
+/// null if `json` is not a valid snapshot. A stopwatch that was running keeps running, with the
+/// time that passed since the snapshot counted as elapsed.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[cfg(feature = "json")]
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_Stopwatch_restoreJson(json: *const c_char) -> *mut c_void {
+    let json = from_raw_string(json).unwrap_or_default();
+    match serde_json::from_str::<StopwatchSnapshot>(&json) {
+        Ok(snapshot) => {
+            Box::into_raw(Box::new(Stopwatch::restore(snapshot))).cast::<std::ffi::c_void>()
+        }
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Boxes a duration to hand it to another language, or returns null if there is none.
+fn into_raw_duration(duration: Option<Duration>) -> *mut c_void {
+    match duration {
+        Some(duration) => Box::into_raw(Box::new(duration)).cast::<std::ffi::c_void>(),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Copies a string to hand it to another language, or returns null if there is none.
+fn into_raw_string(string: Option<&str>) -> *mut c_char {
+    match string {
+        Some(string) => CString::new(string.replace('\0', ""))
+            .expect("Could not create C string from Rust string")
+            .into_raw(),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Copies a string handed over by another language, or returns `None` if it is null.
+///
+/// # Safety
+///
+/// `string` must be null or a valid C string
+unsafe fn from_raw_string(string: *const c_char) -> Option<String> {
+    if string.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(string).to_string_lossy().into_owned())
+    }
+}
+
 /// # Safety
 ///
 /// This function dereferences a raw pointer
@@ -106,7 +418,7 @@ pub unsafe extern "C" fn stopwatch_LapTime_getIndex(
     index: usize,
 ) -> *mut c_void {
     let laptimes = unsafe { std::slice::from_raw_parts_mut(buf.data as *mut LapTime, buf.len) };
-    let laptime = laptimes[index];
+    let laptime = laptimes[index].clone();
     Box::into_raw(Box::new(laptime)).cast::<std::ffi::c_void>()
 }
 
@@ -136,6 +448,41 @@ pub unsafe extern "C" fn stopwatch_LapTime_totalTime(laptime: *mut c_void) -> *m
     Box::into_raw(Box::new(duration)).cast::<std::ffi::c_void>()
 }
 
+/// Returns the label of a lap, or null if it has none. The label is freed with `rust_String_free`.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_LapTime_label(laptime: *mut c_void) -> *mut c_char {
+    into_raw_string((*laptime.cast::<stopwatch::LapTime>()).label())
+}
+
+/// Returns the time of day a lap was taken in ISO 8601 format, or null if it is not known. The
+/// string is freed with `rust_String_free`.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_LapTime_recordedAt(laptime: *mut c_void) -> *mut c_char {
+    into_raw_string(
+        (*laptime.cast::<stopwatch::LapTime>())
+            .recorded_at_iso8601()
+            .as_deref(),
+    )
+}
+
+/// Returns the note of a lap, or null if it has none. The note is freed with `rust_String_free`.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn stopwatch_LapTime_note(laptime: *mut c_void) -> *mut c_char {
+    into_raw_string((*laptime.cast::<stopwatch::LapTime>()).note())
+}
+
 /// # Safety
 ///
 /// This function dereferences a raw pointer
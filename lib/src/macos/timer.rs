@@ -1,6 +1,30 @@
-use std::{ffi::c_void, time::Duration};
+use std::ffi::c_void;
+#[cfg(feature = "json")]
+use std::ffi::{c_char, CStr, CString};
+use std::time::Duration;
 
-use crate::timer::{self, Timer};
+#[cfg(feature = "json")]
+use crate::snapshot::TimerSnapshot;
+use crate::timer::{self, CompletionPolicy, Timer, TimerState, WarningThreshold};
+
+/// A buffer of warning threshold indices returned by a timer, freed with
+/// `timer_WarningBuffer_free`.
+#[repr(C)]
+pub struct WarningBuffer {
+    /// A pointer to the indices stored in the buffer.
+    data: *mut usize,
+    /// The length of the buffer.
+    len: usize,
+}
+
+impl From<Vec<usize>> for WarningBuffer {
+    fn from(indices: Vec<usize>) -> Self {
+        let indices = indices.into_boxed_slice();
+        let len = indices.len();
+        let data = Box::into_raw(indices).cast::<usize>();
+        WarningBuffer { data, len }
+    }
+}
 
 /// # Safety
 ///
@@ -70,6 +94,165 @@ pub unsafe extern "C" fn timer_Timer_duration(timer: *mut c_void) -> *mut c_void
     Box::into_raw(Box::new(duration)).cast::<std::ffi::c_void>()
 }
 
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_Timer_setDuration(timer: *mut c_void, secs: u64) {
+    (*timer.cast::<timer::Timer>()).set_duration(Duration::from_secs(secs));
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_Timer_setRemaining(timer: *mut c_void, secs: u64) {
+    (*timer.cast::<timer::Timer>()).set_remaining(Duration::from_secs(secs));
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_Timer_addTime(timer: *mut c_void, secs: u64) {
+    (*timer.cast::<timer::Timer>()).add_time(Duration::from_secs(secs));
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_Timer_subtractTime(timer: *mut c_void, secs: u64) {
+    (*timer.cast::<timer::Timer>()).subtract_time(Duration::from_secs(secs));
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_Timer_setOvertime(timer: *mut c_void, enabled: bool) {
+    (*timer.cast::<timer::Timer>()).set_overtime(enabled);
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_Timer_isOvertimeEnabled(timer: *mut c_void) -> bool {
+    (*timer.cast::<timer::Timer>()).is_overtime_enabled()
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_Timer_setCompletionPolicy(
+    timer: *mut c_void,
+    policy: CompletionPolicy,
+) {
+    (*timer.cast::<timer::Timer>()).set_completion_policy(policy);
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_Timer_completionPolicy(timer: *mut c_void) -> CompletionPolicy {
+    (*timer.cast::<timer::Timer>()).completion_policy()
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_Timer_addWarningSecs(timer: *mut c_void, secs: u64) {
+    (*timer.cast::<timer::Timer>())
+        .add_warning(WarningThreshold::Remaining(Duration::from_secs(secs)));
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_Timer_addWarningPercent(timer: *mut c_void, percent: u8) {
+    (*timer.cast::<timer::Timer>()).add_warning(WarningThreshold::PercentRemaining(percent));
+}
+
+/// Returns the indices of the thresholds the timer reached since they were last reported, in the
+/// order they were added, and marks them as reported.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_Timer_takeWarnings(timer: *mut c_void) -> WarningBuffer {
+    (*timer.cast::<timer::Timer>()).take_warnings().into()
+}
+
+/// Returns how long the timer runs until it reaches the next threshold that was not reported yet,
+/// or null if it is not running or has no such threshold left.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_Timer_nextWarning(timer: *mut c_void) -> *mut c_void {
+    match (*timer.cast::<timer::Timer>()).next_warning() {
+        Some(duration) => Box::into_raw(Box::new(duration)).cast::<std::ffi::c_void>(),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// # Safety
+///
+/// This function frees a buffer returned by a timer
+#[no_mangle]
+pub unsafe extern "C" fn timer_WarningBuffer_free(buf: WarningBuffer) {
+    let WarningBuffer { data, len } = buf;
+    drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(data, len)));
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_Timer_clearWarnings(timer: *mut c_void) {
+    (*timer.cast::<timer::Timer>()).clear_warnings();
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_Timer_cycle(timer: *mut c_void) -> u32 {
+    (*timer.cast::<timer::Timer>()).cycle()
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_Timer_completions(timer: *mut c_void) -> u32 {
+    (*timer.cast::<timer::Timer>()).completions()
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_Timer_overtime(timer: *mut c_void) -> *mut c_void {
+    let duration = (*timer.cast::<timer::Timer>()).overtime();
+    Box::into_raw(Box::new(duration)).cast::<std::ffi::c_void>()
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_Timer_state(timer: *mut c_void) -> TimerState {
+    (*timer.cast::<timer::Timer>()).state()
+}
+
 /// # Safety
 ///
 /// This function dereferences a raw pointer
@@ -99,6 +282,43 @@ pub unsafe extern "C" fn timer_Timer_hasNotStarted(timer: *mut c_void) -> bool {
 /// This function dereferences a raw pointer
 #[no_mangle]
 pub unsafe extern "C" fn timer_Timer_eq(timer: *mut c_void, other: *mut c_void) -> bool {
-    let other = *other.cast::<timer::Timer>();
-    (*timer.cast::<timer::Timer>()).eq(&other)
+    let other = &*other.cast::<timer::Timer>();
+    (*timer.cast::<timer::Timer>()).eq(other)
+}
+
+/// Returns a snapshot of the timer as JSON, to be restored with `timer_Timer_restoreJson` after
+/// the app restarts. The string is freed with `rust_String_free`, and is null if the snapshot could
+/// not be serialized.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[cfg(feature = "json")]
+#[no_mangle]
+pub unsafe extern "C" fn timer_Timer_snapshotJson(timer: *mut c_void) -> *mut c_char {
+    match serde_json::to_string(&(*timer.cast::<timer::Timer>()).snapshot()) {
+        Ok(json) => CString::new(json)
+            .expect("Could not create C string from Rust string")
+            .into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Creates a timer from a snapshot returned by `timer_Timer_snapshotJson`, or returns null if
+/// `json` is not a valid snapshot. A timer that was running keeps running, with the time that
+/// passed since the snapshot counted as elapsed.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[cfg(feature = "json")]
+#[no_mangle]
+pub unsafe extern "C" fn timer_Timer_restoreJson(json: *const c_char) -> *mut c_void {
+    let json = CStr::from_ptr(json).to_string_lossy();
+    match serde_json::from_str::<TimerSnapshot>(&json) {
+        Ok(snapshot) => {
+            Box::into_raw(Box::new(Timer::restore(snapshot))).cast::<std::ffi::c_void>()
+        }
+        Err(_) => std::ptr::null_mut(),
+    }
 }
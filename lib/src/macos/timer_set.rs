@@ -0,0 +1,183 @@
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::time::Duration;
+
+use crate::timer::{Timer, TimerState};
+use crate::timer_set::{TimerId, TimerSet};
+
+/// A buffer of timer ids returned by a timer set, freed with `timer_set_TimerIdBuffer_free`.
+#[repr(C)]
+pub struct TimerIdBuffer {
+    /// A pointer to the ids stored in the buffer.
+    data: *mut u64,
+    /// The length of the buffer.
+    len: usize,
+}
+
+impl From<Vec<TimerId>> for TimerIdBuffer {
+    fn from(ids: Vec<TimerId>) -> Self {
+        let ids: Box<[u64]> = ids.into_iter().map(TimerId::as_u64).collect();
+        let len = ids.len();
+        let data = Box::into_raw(ids).cast::<u64>();
+        TimerIdBuffer { data, len }
+    }
+}
+
+/// A constructor that creates a new empty `TimerSet`.
+#[no_mangle]
+pub extern "C" fn timer_set_TimerSet_create() -> *mut c_void {
+    Box::into_raw(Box::new(TimerSet::new())).cast::<std::ffi::c_void>()
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer and frees the object
+#[no_mangle]
+pub unsafe extern "C" fn timer_set_TimerSet_free(set: *mut c_void) {
+    drop(Box::from_raw(set.cast::<TimerSet>()));
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer, and `label` must be a valid C string
+#[no_mangle]
+pub unsafe extern "C" fn timer_set_TimerSet_add(
+    set: *mut c_void,
+    label: *const c_char,
+    secs: u64,
+) -> u64 {
+    let label = CStr::from_ptr(label).to_string_lossy();
+    (*set.cast::<TimerSet>())
+        .add(label, Duration::from_secs(secs))
+        .as_u64()
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_set_TimerSet_remove(set: *mut c_void, id: u64) -> bool {
+    (*set.cast::<TimerSet>()).remove(id.into()).is_some()
+}
+
+/// Returns the label of a timer, or null if there is no such timer. The label is freed with
+/// `rust_String_free`.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_set_TimerSet_label(set: *mut c_void, id: u64) -> *mut c_char {
+    match (*set.cast::<TimerSet>()).label(id.into()) {
+        Some(label) => CString::new(label.replace('\0', ""))
+            .expect("Could not create C string from Rust string")
+            .into_raw(),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_set_TimerSet_start(set: *mut c_void, id: u64) -> bool {
+    (*set.cast::<TimerSet>())
+        .get_mut(id.into())
+        .map(Timer::start)
+        .is_some()
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_set_TimerSet_stop(set: *mut c_void, id: u64) -> bool {
+    (*set.cast::<TimerSet>())
+        .get_mut(id.into())
+        .map(Timer::stop)
+        .is_some()
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_set_TimerSet_reset(set: *mut c_void, id: u64) -> bool {
+    (*set.cast::<TimerSet>())
+        .get_mut(id.into())
+        .map(Timer::reset)
+        .is_some()
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_set_TimerSet_startAll(set: *mut c_void) {
+    (*set.cast::<TimerSet>()).start_all();
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_set_TimerSet_pauseAll(set: *mut c_void) {
+    (*set.cast::<TimerSet>()).pause_all();
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_set_TimerSet_resumeAll(set: *mut c_void) {
+    (*set.cast::<TimerSet>()).resume_all();
+}
+
+/// Returns the remaining time of a timer, or null if there is no such timer.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_set_TimerSet_remaining(set: *mut c_void, id: u64) -> *mut c_void {
+    match (*set.cast::<TimerSet>()).get(id.into()) {
+        Some(timer) => Box::into_raw(Box::new(timer.remaining())).cast::<std::ffi::c_void>(),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Returns the state of a timer, which is `Idle` if there is no such timer.
+///
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_set_TimerSet_state(set: *mut c_void, id: u64) -> TimerState {
+    (*set.cast::<TimerSet>())
+        .get(id.into())
+        .map_or(TimerState::Idle, Timer::state)
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_set_TimerSet_byNextExpiry(set: *mut c_void) -> TimerIdBuffer {
+    (*set.cast::<TimerSet>()).by_next_expiry().into()
+}
+
+/// # Safety
+///
+/// This function dereferences a raw pointer
+#[no_mangle]
+pub unsafe extern "C" fn timer_set_TimerSet_takeExpired(set: *mut c_void) -> TimerIdBuffer {
+    (*set.cast::<TimerSet>()).take_expired().into()
+}
+
+/// # Safety
+///
+/// This function frees a buffer returned by a timer set
+#[no_mangle]
+pub unsafe extern "C" fn timer_set_TimerIdBuffer_free(buf: TimerIdBuffer) {
+    let TimerIdBuffer { data, len } = buf;
+    drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(data, len)));
+}
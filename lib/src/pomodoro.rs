@@ -0,0 +1,514 @@
+//! A module that defines a Pomodoro timer
+//!
+//! A [`Pomodoro`] alternates focus sessions with short breaks, and takes a long break after every
+//! few focus sessions. It records interruptions and counts the focus sessions completed each day.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Local, NaiveDate};
+
+use crate::time_source::{SystemTimeSource, TimeSource};
+use crate::timer::{Timer, TimerState};
+
+/// The kind of session a [`Pomodoro`] is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Session {
+    /// A focus session, the pomodoro itself.
+    Focus,
+    /// The break after a focus session.
+    ShortBreak,
+    /// The longer break after every few focus sessions.
+    LongBreak,
+}
+
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Session::Focus => "focus",
+            Session::ShortBreak => "short break",
+            Session::LongBreak => "long break",
+        })
+    }
+}
+
+/// The settings of a [`Pomodoro`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PomodoroSettings {
+    /// The length of a focus session.
+    pub focus: Duration,
+    /// The length of a short break.
+    pub short_break: Duration,
+    /// The length of a long break.
+    pub long_break: Duration,
+    /// The number of focus sessions after which a long break is taken instead of a short one, or
+    /// zero to never take a long break.
+    pub long_break_every: u32,
+    /// Whether a break starts on its own once a focus session is over.
+    pub auto_start_breaks: bool,
+    /// Whether a focus session starts on its own once a break is over.
+    pub auto_start_focus: bool,
+}
+
+impl PomodoroSettings {
+    /// Returns the length of the given kind of session.
+    #[must_use]
+    pub fn duration(&self, session: Session) -> Duration {
+        match session {
+            Session::Focus => self.focus,
+            Session::ShortBreak => self.short_break,
+            Session::LongBreak => self.long_break,
+        }
+    }
+
+    /// Returns whether the given kind of session starts on its own once the one before it is over.
+    #[must_use]
+    pub fn auto_starts(&self, session: Session) -> bool {
+        match session {
+            Session::Focus => self.auto_start_focus,
+            Session::ShortBreak | Session::LongBreak => self.auto_start_breaks,
+        }
+    }
+}
+
+impl Default for PomodoroSettings {
+    /// The classic settings: 25 minutes of focus, 5 minute breaks and a 15 minute break after
+    /// every 4 focus sessions, with nothing starting on its own.
+    fn default() -> Self {
+        Self {
+            focus: Duration::from_mins(25),
+            short_break: Duration::from_mins(5),
+            long_break: Duration::from_mins(15),
+            long_break_every: 4,
+            auto_start_breaks: false,
+            auto_start_focus: false,
+        }
+    }
+}
+
+/// Where an interruption of a focus session came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InterruptionKind {
+    /// The interruption came from the person focusing, e.g. a sudden urge to check mail.
+    Internal,
+    /// The interruption came from someone or something else, e.g. a phone call.
+    External,
+}
+
+/// An interruption recorded during a focus session, see [`Pomodoro::interrupt`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Interruption {
+    /// Where the interruption came from.
+    kind: InterruptionKind,
+    /// The wall-clock time of the interruption.
+    at: SystemTime,
+    /// How far into the focus session the interruption happened.
+    elapsed: Duration,
+}
+
+impl Interruption {
+    /// Returns where the interruption came from.
+    #[must_use]
+    pub fn kind(&self) -> InterruptionKind {
+        self.kind
+    }
+
+    /// Returns the wall-clock time of the interruption.
+    #[must_use]
+    pub fn at(&self) -> SystemTime {
+        self.at
+    }
+
+    /// Returns how far into the focus session the interruption happened.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+/// A Pomodoro timer that moves from focus sessions to breaks and back.
+///
+/// The timer of the current session runs on its own, but moving on to the next session happens in
+/// [`Pomodoro::update`], which front-ends should call regularly, e.g. on every tick of their
+/// display. A session that starts on its own starts at the exact moment the previous one ran out,
+/// however late `update` is called.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use clock::pomodoro::{Pomodoro, PomodoroSettings, Session};
+/// use clock::time_source::MockTimeSource;
+///
+/// let clock = MockTimeSource::new();
+/// let settings = PomodoroSettings {
+///     auto_start_breaks: true,
+///     ..PomodoroSettings::default()
+/// };
+/// let mut pomodoro = Pomodoro::with_time_source(settings, clock.clone());
+///
+/// pomodoro.start();
+/// clock.advance(Duration::from_secs(26 * 60));
+///
+/// assert_eq!(pomodoro.update(), vec![Session::Focus]);
+/// assert_eq!(pomodoro.session(), Session::ShortBreak);
+/// assert_eq!(pomodoro.remaining(), Duration::from_secs(4 * 60));
+/// assert_eq!(pomodoro.completed_today(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Pomodoro<T: TimeSource + Clone = SystemTimeSource> {
+    /// The settings used for every new session.
+    settings: PomodoroSettings,
+    /// The kind of the current session.
+    session: Session,
+    /// The timer of the current session.
+    timer: Timer<T>,
+    /// The number of focus sessions completed since the last long break.
+    completed_in_cycle: u32,
+    /// The interruptions recorded so far.
+    interruptions: Vec<Interruption>,
+    /// The number of focus sessions completed on each day, in local time.
+    completed_per_day: BTreeMap<NaiveDate, u32>,
+    /// The [`TimeSource`] given to the timer of every session.
+    time_source: T,
+}
+
+impl Pomodoro {
+    /// Creates a new [`Pomodoro`] that reads the system clock, ready to start a focus session.
+    #[must_use]
+    pub fn new(settings: PomodoroSettings) -> Self {
+        Self::with_time_source(settings, SystemTimeSource)
+    }
+}
+
+impl Default for Pomodoro {
+    fn default() -> Self {
+        Self::new(PomodoroSettings::default())
+    }
+}
+
+impl<T: TimeSource + Clone> Pomodoro<T> {
+    /// Creates a new [`Pomodoro`] that reads the current time from `time_source`, ready to start a
+    /// focus session.
+    #[must_use]
+    pub fn with_time_source(settings: PomodoroSettings, time_source: T) -> Self {
+        Self {
+            timer: Self::session_timer(&settings, Session::Focus, &time_source),
+            settings,
+            session: Session::Focus,
+            completed_in_cycle: 0,
+            interruptions: Vec::new(),
+            completed_per_day: BTreeMap::new(),
+            time_source,
+        }
+    }
+
+    /// Returns the settings used for every new session.
+    #[must_use]
+    pub fn settings(&self) -> PomodoroSettings {
+        self.settings
+    }
+
+    /// Changes the settings. The current session keeps its length unless it has not been started
+    /// yet.
+    pub fn set_settings(&mut self, settings: PomodoroSettings) {
+        self.settings = settings;
+        if self.timer.state() == TimerState::Idle {
+            self.timer.set_duration(settings.duration(self.session));
+        }
+    }
+
+    /// Returns the kind of the current session.
+    #[must_use]
+    pub fn session(&self) -> Session {
+        self.session
+    }
+
+    /// Returns the timer of the current session.
+    #[must_use]
+    pub fn timer(&self) -> &Timer<T> {
+        &self.timer
+    }
+
+    /// Returns the state of the current session.
+    #[must_use]
+    pub fn state(&self) -> TimerState {
+        self.timer.state()
+    }
+
+    /// Returns the time left in the current session.
+    #[must_use]
+    pub fn remaining(&self) -> Duration {
+        self.timer.remaining()
+    }
+
+    /// Starts or resumes the current session.
+    pub fn start(&mut self) {
+        self.timer.start();
+    }
+
+    /// Pauses the current session.
+    pub fn stop(&mut self) {
+        self.timer.stop();
+    }
+
+    /// Stops the current session and puts it back to its full length.
+    pub fn reset(&mut self) {
+        self.timer.reset();
+        self.timer
+            .set_duration(self.settings.duration(self.session));
+    }
+
+    /// Ends the current session early and moves on to the next one. A skipped focus session does
+    /// not count as completed.
+    pub fn skip(&mut self) {
+        self.advance(Duration::ZERO);
+    }
+
+    /// Moves on to the next session for every session that ran out since the last call, and
+    /// returns the kinds of those sessions in order.
+    pub fn update(&mut self) -> Vec<Session> {
+        let mut completed = Vec::new();
+        while self.timer.state() == TimerState::Expired {
+            let late = self.timer.overtime();
+            if self.session == Session::Focus {
+                let now = self.time_source.wall_clock();
+                let date =
+                    DateTime::<Local>::from(now.checked_sub(late).unwrap_or(now)).date_naive();
+                *self.completed_per_day.entry(date).or_default() += 1;
+                self.completed_in_cycle += 1;
+            }
+            completed.push(self.session);
+            self.advance(late);
+
+            // A session that is over as soon as it starts is left for the next call, so that
+            // zero-length sessions that start on their own do not keep completing each other.
+            if late.is_zero() || self.timer.duration().is_zero() {
+                break;
+            }
+        }
+        completed
+    }
+
+    /// Records an interruption of the current focus session. Returns `false` and records nothing
+    /// if no focus session is under way.
+    pub fn interrupt(&mut self, kind: InterruptionKind) -> bool {
+        if self.session != Session::Focus
+            || !matches!(self.timer.state(), TimerState::Running | TimerState::Paused)
+        {
+            return false;
+        }
+        self.interruptions.push(Interruption {
+            kind,
+            at: self.time_source.wall_clock(),
+            elapsed: self.timer.elapsed(),
+        });
+        true
+    }
+
+    /// Returns the interruptions recorded so far, oldest first.
+    #[must_use]
+    pub fn interruptions(&self) -> &[Interruption] {
+        &self.interruptions
+    }
+
+    /// Returns the number of focus sessions completed in total.
+    #[must_use]
+    pub fn completed(&self) -> u32 {
+        self.completed_per_day.values().sum()
+    }
+
+    /// Returns the number of focus sessions completed on the given day, in local time.
+    #[must_use]
+    pub fn completed_on(&self, date: NaiveDate) -> u32 {
+        self.completed_per_day
+            .get(&date)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the number of focus sessions completed today, in local time.
+    #[must_use]
+    pub fn completed_today(&self) -> u32 {
+        self.completed_on(DateTime::<Local>::from(self.time_source.wall_clock()).date_naive())
+    }
+
+    /// Returns every day on which focus sessions were completed along with their number, oldest
+    /// first.
+    pub fn completed_per_day(&self) -> impl Iterator<Item = (NaiveDate, u32)> + '_ {
+        self.completed_per_day
+            .iter()
+            .map(|(&date, &count)| (date, count))
+    }
+
+    /// Returns the number of focus sessions left until the next long break, or `None` if long
+    /// breaks are turned off.
+    #[must_use]
+    pub fn until_long_break(&self) -> Option<u32> {
+        (self.settings.long_break_every > 0).then(|| {
+            self.settings
+                .long_break_every
+                .saturating_sub(self.completed_in_cycle)
+        })
+    }
+
+    /// Moves on to the session after the current one, starting it `late` after it was due if it
+    /// starts on its own.
+    fn advance(&mut self, late: Duration) {
+        self.session = match self.session {
+            Session::ShortBreak | Session::LongBreak => Session::Focus,
+            Session::Focus if self.until_long_break() == Some(0) => {
+                self.completed_in_cycle = 0;
+                Session::LongBreak
+            }
+            Session::Focus => Session::ShortBreak,
+        };
+        self.timer = Self::session_timer(&self.settings, self.session, &self.time_source);
+        if self.settings.auto_starts(self.session) {
+            self.timer.start_late(late);
+        }
+    }
+
+    /// Creates the timer of a session, which keeps counting once it runs out so that the next
+    /// session can start exactly when it was due.
+    fn session_timer(settings: &PomodoroSettings, session: Session, time_source: &T) -> Timer<T> {
+        let mut timer = Timer::with_time_source(settings.duration(session), time_source.clone());
+        timer.set_overtime(true);
+        timer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InterruptionKind, Pomodoro, PomodoroSettings, Session};
+    use crate::time_source::{MockTimeSource, TimeSource};
+    use crate::timer::TimerState;
+
+    use chrono::{DateTime, Local};
+    use std::time::{Duration, SystemTime};
+
+    fn mock_pomodoro(settings: PomodoroSettings) -> (Pomodoro<MockTimeSource>, MockTimeSource) {
+        let clock = MockTimeSource::new();
+        (Pomodoro::with_time_source(settings, clock.clone()), clock)
+    }
+
+    #[test]
+    fn test_long_break_every_n() {
+        let (mut pomodoro, clock) = mock_pomodoro(PomodoroSettings {
+            long_break_every: 2,
+            ..PomodoroSettings::default()
+        });
+        let mut sessions = Vec::new();
+        for _ in 0..5 {
+            sessions.push(pomodoro.session());
+            pomodoro.start();
+            clock.advance(pomodoro.remaining());
+            pomodoro.update();
+            assert_eq!(pomodoro.state(), TimerState::Idle);
+        }
+        assert_eq!(
+            sessions,
+            vec![
+                Session::Focus,
+                Session::ShortBreak,
+                Session::Focus,
+                Session::LongBreak,
+                Session::Focus,
+            ]
+        );
+        assert_eq!(pomodoro.session(), Session::ShortBreak);
+        assert_eq!(pomodoro.completed(), 3);
+        assert_eq!(pomodoro.until_long_break(), Some(1));
+    }
+
+    #[test]
+    fn test_auto_start_does_not_drift() {
+        let (mut pomodoro, clock) = mock_pomodoro(PomodoroSettings {
+            auto_start_breaks: true,
+            auto_start_focus: true,
+            ..PomodoroSettings::default()
+        });
+        pomodoro.start();
+        clock.advance(Duration::from_mins(25 + 5 + 25 + 5 + 3));
+
+        assert_eq!(
+            pomodoro.update(),
+            vec![
+                Session::Focus,
+                Session::ShortBreak,
+                Session::Focus,
+                Session::ShortBreak
+            ]
+        );
+        assert_eq!(pomodoro.session(), Session::Focus);
+        assert_eq!(pomodoro.state(), TimerState::Running);
+        assert_eq!(pomodoro.remaining(), Duration::from_mins(22));
+
+        pomodoro.skip();
+        assert_eq!(pomodoro.session(), Session::ShortBreak);
+        assert_eq!(pomodoro.completed(), 2);
+    }
+
+    #[test]
+    fn test_zero_length_sessions() {
+        let (mut pomodoro, clock) = mock_pomodoro(PomodoroSettings {
+            focus: Duration::ZERO,
+            short_break: Duration::ZERO,
+            long_break: Duration::ZERO,
+            auto_start_breaks: true,
+            auto_start_focus: true,
+            ..PomodoroSettings::default()
+        });
+        pomodoro.start();
+        clock.advance(Duration::from_secs(1));
+
+        assert_eq!(pomodoro.update(), vec![Session::Focus]);
+        assert_eq!(pomodoro.session(), Session::ShortBreak);
+        assert_eq!(pomodoro.update(), vec![Session::ShortBreak]);
+        assert_eq!(pomodoro.completed(), 1);
+    }
+
+    #[test]
+    fn test_interruptions() {
+        let (mut pomodoro, clock) = mock_pomodoro(PomodoroSettings::default());
+        assert!(!pomodoro.interrupt(InterruptionKind::Internal));
+
+        pomodoro.start();
+        clock.advance(Duration::from_mins(10));
+        assert!(pomodoro.interrupt(InterruptionKind::External));
+        pomodoro.skip();
+        assert!(!pomodoro.interrupt(InterruptionKind::Internal));
+
+        let interruptions = pomodoro.interruptions();
+        assert_eq!(interruptions.len(), 1);
+        assert_eq!(interruptions[0].kind(), InterruptionKind::External);
+        assert_eq!(interruptions[0].elapsed(), Duration::from_mins(10));
+        assert_eq!(interruptions[0].at(), clock.wall_clock());
+    }
+
+    #[test]
+    fn test_per_day_count() {
+        let clock =
+            MockTimeSource::starting_at(SystemTime::UNIX_EPOCH + Duration::from_hours(24 * 20_000));
+        let mut pomodoro = Pomodoro::with_time_source(PomodoroSettings::default(), clock.clone());
+        let date = |time: SystemTime| DateTime::<Local>::from(time).date_naive();
+
+        pomodoro.start();
+        clock.advance(Duration::from_mins(25));
+        pomodoro.update();
+        let first_day = date(clock.wall_clock());
+        assert_eq!(pomodoro.completed_today(), 1);
+
+        pomodoro.skip();
+        clock.advance(Duration::from_hours(24));
+        pomodoro.start();
+        clock.advance(Duration::from_mins(25));
+        pomodoro.update();
+
+        assert_eq!(pomodoro.completed_on(first_day), 1);
+        assert_eq!(pomodoro.completed_today(), 1);
+        assert_eq!(pomodoro.completed(), 2);
+        assert_eq!(pomodoro.completed_per_day().count(), 2);
+    }
+}
@@ -0,0 +1,681 @@
+//! A module that defines race timing for many competitors on one clock
+//!
+//! A [`Race`] runs a single race clock, started once for everybody or with staggered start
+//! times, and records the splits and finishes of competitors by [`Bib`] number. The records are
+//! kept in the order they were entered so a mis-entered bib can be corrected afterwards, and the
+//! splits and ranked results of every competitor are derived from them.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::error::ClockError;
+use crate::stopwatch::{LapTime, Stopwatch};
+use crate::time_source::{SystemTimeSource, TimeSource};
+
+/// The bib number of a competitor in a [`Race`].
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bib(u32);
+
+impl Bib {
+    /// Returns the bib as a number, e.g. to hand it to another language.
+    #[must_use]
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+}
+
+impl From<u32> for Bib {
+    fn from(bib: u32) -> Self {
+        Bib(bib)
+    }
+}
+
+impl fmt::Display for Bib {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&self.0.to_string())
+    }
+}
+
+impl FromStr for Bib {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Bib)
+    }
+}
+
+/// An error returned when a [`Race`] cannot register a competitor or record a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RaceError {
+    /// The race clock could not be read.
+    Clock(ClockError),
+    /// No competitor wears this bib.
+    UnknownBib(Bib),
+    /// Another competitor already wears this bib.
+    DuplicateBib(Bib),
+    /// The competitor already finished, so no later time can be recorded for them.
+    AlreadyFinished(Bib),
+    /// The competitor had not started yet at the time being recorded.
+    NotStarted(Bib),
+    /// There is no record with this index.
+    UnknownRecord(usize),
+}
+
+impl fmt::Display for RaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RaceError::Clock(err) => err.fmt(f),
+            RaceError::UnknownBib(bib) => write!(f, "no competitor wears bib {bib}"),
+            RaceError::DuplicateBib(bib) => write!(f, "bib {bib} is already taken"),
+            RaceError::AlreadyFinished(bib) => write!(f, "bib {bib} has already finished"),
+            RaceError::NotStarted(bib) => write!(f, "bib {bib} has not started yet"),
+            RaceError::UnknownRecord(index) => write!(f, "there is no record {index}"),
+        }
+    }
+}
+
+impl std::error::Error for RaceError {}
+
+impl From<ClockError> for RaceError {
+    fn from(err: ClockError) -> Self {
+        RaceError::Clock(err)
+    }
+}
+
+/// A competitor registered in a [`Race`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Competitor {
+    /// The name shown for the competitor.
+    name: String,
+    /// The race time at which the competitor starts.
+    start_offset: Duration,
+}
+
+/// A time recorded for a competitor, see [`Race::records`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Record {
+    /// The bib the time was recorded for.
+    bib: Bib,
+    /// The time on the race clock when it was recorded.
+    race_time: Duration,
+    /// Whether the competitor finished, rather than passed an intermediate split.
+    finish: bool,
+}
+
+impl Record {
+    /// Returns the bib the time was recorded for.
+    #[must_use]
+    pub fn bib(&self) -> Bib {
+        self.bib
+    }
+
+    /// Returns the time on the race clock when it was recorded.
+    #[must_use]
+    pub fn race_time(&self) -> Duration {
+        self.race_time
+    }
+
+    /// Returns `true` if the record is a finish rather than an intermediate split.
+    #[must_use]
+    pub fn is_finish(&self) -> bool {
+        self.finish
+    }
+}
+
+/// The result of a competitor in a [`Race`], see [`Race::results`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaceResult {
+    /// The position of the competitor, if they finished.
+    position: Option<usize>,
+    /// The bib of the competitor.
+    bib: Bib,
+    /// The name of the competitor.
+    name: String,
+    /// The splits of the competitor, ending with their finish if they finished.
+    splits: Vec<LapTime>,
+    /// The finish time of the competitor, if they finished.
+    time: Option<Duration>,
+    /// How much longer than the winner the competitor took, if they finished.
+    gap: Option<Duration>,
+}
+
+impl RaceResult {
+    /// Returns the position of the competitor, starting at 1 for the winner, or `None` if they did
+    /// not finish. Competitors with the same time share a position.
+    #[must_use]
+    pub fn position(&self) -> Option<usize> {
+        self.position
+    }
+
+    /// Returns the bib of the competitor.
+    #[must_use]
+    pub fn bib(&self) -> Bib {
+        self.bib
+    }
+
+    /// Returns the name of the competitor.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the splits of the competitor, ending with their finish if they finished.
+    #[must_use]
+    pub fn splits(&self) -> &[LapTime] {
+        &self.splits
+    }
+
+    /// Returns the time the competitor took from their own start to the finish, or `None` if they
+    /// did not finish.
+    #[must_use]
+    pub fn time(&self) -> Option<Duration> {
+        self.time
+    }
+
+    /// Returns how much longer than the winner the competitor took, or `None` if they did not
+    /// finish.
+    #[must_use]
+    pub fn gap(&self) -> Option<Duration> {
+        self.gap
+    }
+}
+
+/// A race in which many competitors are timed on one race clock.
+///
+/// Every competitor is timed from their own start, which is the start of the race clock unless
+/// they were given a later one with [`Race::set_start_offset`] or [`Race::set_interval_starts`].
+/// Their splits follow the semantics of [`LapTime`]: the split time is measured from the previous
+/// split and the total time from the start of the competitor.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use clock::race::Race;
+/// use clock::time_source::MockTimeSource;
+///
+/// let clock = MockTimeSource::new();
+/// let mut race = Race::with_time_source(clock.clone());
+/// race.add_competitor(7.into(), "Ada").unwrap();
+/// race.add_competitor(12.into(), "Grace").unwrap();
+/// race.start();
+///
+/// clock.advance(Duration::from_secs(95));
+/// race.record_finish(12.into()).unwrap();
+/// clock.advance(Duration::from_secs(4));
+/// race.record_finish(7.into()).unwrap();
+///
+/// let results = race.results();
+/// assert_eq!(results[0].name(), "Grace");
+/// assert_eq!(results[1].position(), Some(2));
+/// assert_eq!(results[1].gap(), Some(Duration::from_secs(4)));
+/// ```
+#[derive(Debug)]
+pub struct Race<T: TimeSource = SystemTimeSource> {
+    /// The race clock.
+    clock: Stopwatch<T>,
+    /// The registered competitors, keyed by bib.
+    competitors: BTreeMap<Bib, Competitor>,
+    /// The recorded splits and finishes, in the order they were entered.
+    records: Vec<Record>,
+}
+
+impl Race {
+    /// Creates a race without competitors whose clock reads the system clock.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_time_source(SystemTimeSource)
+    }
+}
+
+impl Default for Race {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: TimeSource> Race<T> {
+    /// Creates a race without competitors whose clock reads the current time from `time_source`.
+    #[must_use]
+    pub fn with_time_source(time_source: T) -> Self {
+        Self {
+            clock: Stopwatch::with_time_source(time_source),
+            competitors: BTreeMap::new(),
+            records: Vec::new(),
+        }
+    }
+
+    /// Returns the race clock, e.g. to show the countdown to a delayed start.
+    #[must_use]
+    pub fn clock(&self) -> &Stopwatch<T> {
+        &self.clock
+    }
+
+    /// Starts the race clock. If it is already running, this method does nothing.
+    pub fn start(&mut self) {
+        self.clock.start();
+    }
+
+    /// Starts the race clock once `countdown` has passed, see [`Stopwatch::start_after`].
+    pub fn start_after(&mut self, countdown: Duration) {
+        self.clock.start_after(countdown);
+    }
+
+    /// Returns the time on the race clock.
+    #[must_use]
+    pub fn race_time(&self) -> Duration {
+        self.clock.elapsed_time()
+    }
+
+    /// Registers a competitor who starts with the race clock.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RaceError::DuplicateBib`] if another competitor already wears `bib`.
+    pub fn add_competitor(&mut self, bib: Bib, name: impl Into<String>) -> Result<(), RaceError> {
+        if self.competitors.contains_key(&bib) {
+            return Err(RaceError::DuplicateBib(bib));
+        }
+
+        self.competitors.insert(
+            bib,
+            Competitor {
+                name: name.into(),
+                start_offset: Duration::ZERO,
+            },
+        );
+        Ok(())
+    }
+
+    /// Returns the name of the competitor wearing `bib`, or `None` if there is none.
+    #[must_use]
+    pub fn name(&self, bib: Bib) -> Option<&str> {
+        self.competitors
+            .get(&bib)
+            .map(|competitor| competitor.name.as_str())
+    }
+
+    /// Returns the race time at which the competitor wearing `bib` starts, or `None` if there is
+    /// none.
+    #[must_use]
+    pub fn start_offset(&self, bib: Bib) -> Option<Duration> {
+        self.competitors
+            .get(&bib)
+            .map(|competitor| competitor.start_offset)
+    }
+
+    /// Makes the competitor wearing `bib` start when the race clock shows `start_offset`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RaceError::UnknownBib`] if no competitor wears `bib`, or
+    /// [`RaceError::NotStarted`] if a time was already recorded for them before `start_offset`.
+    /// The start is left unchanged in both cases.
+    pub fn set_start_offset(&mut self, bib: Bib, start_offset: Duration) -> Result<(), RaceError> {
+        let competitor = self
+            .competitors
+            .get_mut(&bib)
+            .ok_or(RaceError::UnknownBib(bib))?;
+        let previous = std::mem::replace(&mut competitor.start_offset, start_offset);
+        self.validate().inspect_err(|_| {
+            if let Some(competitor) = self.competitors.get_mut(&bib) {
+                competitor.start_offset = previous;
+            }
+        })
+    }
+
+    /// Staggers the starts of all competitors, in bib order, `interval` apart. The first
+    /// competitor starts with the race clock.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RaceError::NotStarted`] if a time was already recorded for a competitor before
+    /// their new start. The starts are left unchanged in that case.
+    pub fn set_interval_starts(&mut self, interval: Duration) -> Result<(), RaceError> {
+        let previous = self.competitors.clone();
+        let mut start_offset = Duration::ZERO;
+        for competitor in self.competitors.values_mut() {
+            competitor.start_offset = start_offset;
+            start_offset = start_offset.saturating_add(interval);
+        }
+        self.validate().inspect_err(|_| self.competitors = previous)
+    }
+
+    /// Records that the competitor wearing `bib` passed an intermediate split now, and returns
+    /// the split.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RaceError::Clock`] if the race clock is not running, [`RaceError::UnknownBib`] if
+    /// no competitor wears `bib`, [`RaceError::NotStarted`] if they have not started yet, or
+    /// [`RaceError::AlreadyFinished`] if they have finished.
+    pub fn record_split(&mut self, bib: Bib) -> Result<LapTime, RaceError> {
+        let race_time = self.running_race_time()?;
+        self.record_split_at(bib, race_time)
+    }
+
+    /// Records that the competitor wearing `bib` passed an intermediate split when the race clock
+    /// showed `race_time`, e.g. when entering times from a paper sheet, and returns the split.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Race::record_split`], except that the race clock does not have to be running.
+    pub fn record_split_at(&mut self, bib: Bib, race_time: Duration) -> Result<LapTime, RaceError> {
+        self.push_record(Record {
+            bib,
+            race_time,
+            finish: false,
+        })
+    }
+
+    /// Records that the competitor wearing `bib` finished now, and returns their finish as their
+    /// last split.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Race::record_split`].
+    pub fn record_finish(&mut self, bib: Bib) -> Result<LapTime, RaceError> {
+        let race_time = self.running_race_time()?;
+        self.record_finish_at(bib, race_time)
+    }
+
+    /// Records that the competitor wearing `bib` finished when the race clock showed `race_time`,
+    /// and returns their finish as their last split.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Race::record_split_at`].
+    pub fn record_finish_at(
+        &mut self,
+        bib: Bib,
+        race_time: Duration,
+    ) -> Result<LapTime, RaceError> {
+        self.push_record(Record {
+            bib,
+            race_time,
+            finish: true,
+        })
+    }
+
+    /// Returns the recorded splits and finishes in the order they were entered.
+    #[must_use]
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    /// Moves the record at `index` of [`Race::records`] to the competitor wearing `bib`, e.g.
+    /// after a bib was misread at the finish.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RaceError::UnknownRecord`] if there is no record at `index`, or any error
+    /// [`Race::record_split_at`] would return for the corrected record. The record is left
+    /// unchanged in both cases.
+    pub fn correct_bib(&mut self, index: usize, bib: Bib) -> Result<(), RaceError> {
+        let record = self
+            .records
+            .get_mut(index)
+            .ok_or(RaceError::UnknownRecord(index))?;
+        let previous = std::mem::replace(&mut record.bib, bib);
+        self.validate().inspect_err(|_| {
+            self.records[index].bib = previous;
+        })
+    }
+
+    /// Removes the record at `index` of [`Race::records`], e.g. one entered twice, and returns
+    /// it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RaceError::UnknownRecord`] if there is no record at `index`.
+    pub fn remove_record(&mut self, index: usize) -> Result<Record, RaceError> {
+        if index >= self.records.len() {
+            return Err(RaceError::UnknownRecord(index));
+        }
+        Ok(self.records.remove(index))
+    }
+
+    /// Returns the splits of the competitor wearing `bib`, ending with their finish if they
+    /// finished, or `None` if no competitor wears `bib`.
+    #[must_use]
+    pub fn splits(&self, bib: Bib) -> Option<Vec<LapTime>> {
+        let competitor = self.competitors.get(&bib)?;
+        let mut total_times: Vec<_> = self
+            .records
+            .iter()
+            .filter(|record| record.bib == bib)
+            .map(|record| record.race_time.saturating_sub(competitor.start_offset))
+            .collect();
+        total_times.sort_unstable();
+
+        let mut previous_total_time = Duration::ZERO;
+        Some(
+            total_times
+                .into_iter()
+                .enumerate()
+                .map(|(index, total_time)| {
+                    let split_time = total_time.saturating_sub(previous_total_time);
+                    previous_total_time = total_time;
+                    LapTime::new(index + 1, split_time, total_time)
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns the finish time of the competitor wearing `bib`, measured from their own start, or
+    /// `None` if they have not finished.
+    #[must_use]
+    pub fn finish_time(&self, bib: Bib) -> Option<Duration> {
+        let competitor = self.competitors.get(&bib)?;
+        self.records
+            .iter()
+            .find(|record| record.bib == bib && record.finish)
+            .map(|record| record.race_time.saturating_sub(competitor.start_offset))
+    }
+
+    /// Returns the results of all competitors: the finishers ranked by finish time, then those who
+    /// have not finished in bib order.
+    #[must_use]
+    pub fn results(&self) -> Vec<RaceResult> {
+        let mut results: Vec<_> = self
+            .competitors
+            .iter()
+            .map(|(&bib, competitor)| RaceResult {
+                position: None,
+                bib,
+                name: competitor.name.clone(),
+                splits: self.splits(bib).unwrap_or_default(),
+                time: self.finish_time(bib),
+                gap: None,
+            })
+            .collect();
+        results.sort_by_key(|result| (result.time.is_none(), result.time, result.bib));
+
+        let winner = results.first().and_then(|result| result.time);
+        let mut previous_time = None;
+        let mut position = 0;
+        for (index, result) in results.iter_mut().enumerate() {
+            let (Some(time), Some(winner)) = (result.time, winner) else {
+                break;
+            };
+            if previous_time != Some(time) {
+                position = index + 1;
+            }
+            previous_time = Some(time);
+            result.position = Some(position);
+            result.gap = Some(time.saturating_sub(winner));
+        }
+        results
+    }
+
+    /// Returns the time on the race clock, which must be running.
+    fn running_race_time(&self) -> Result<Duration, RaceError> {
+        if !self.clock.is_running() {
+            return Err(ClockError::NotRunning.into());
+        }
+        Ok(self.clock.try_elapsed_time()?)
+    }
+
+    /// Adds `record` if it keeps the records valid, and returns the split it adds.
+    fn push_record(&mut self, record: Record) -> Result<LapTime, RaceError> {
+        self.records.push(record);
+        if let Err(err) = self.validate() {
+            self.records.pop();
+            return Err(err);
+        }
+
+        let start_offset = self.start_offset(record.bib).unwrap_or_default();
+        let total_time = record.race_time.saturating_sub(start_offset);
+        Ok(self
+            .splits(record.bib)
+            .unwrap_or_default()
+            .into_iter()
+            .rfind(|split| split.total_time() == total_time)
+            .unwrap_or_else(|| LapTime::new(1, total_time, total_time)))
+    }
+
+    /// Checks that every record is for a registered competitor who had started by then, and that
+    /// every competitor has at most one finish and no split from their finish on.
+    fn validate(&self) -> Result<(), RaceError> {
+        let mut finishes = BTreeMap::new();
+        for record in &self.records {
+            let competitor = self
+                .competitors
+                .get(&record.bib)
+                .ok_or(RaceError::UnknownBib(record.bib))?;
+            if record.race_time < competitor.start_offset {
+                return Err(RaceError::NotStarted(record.bib));
+            }
+            if record.finish && finishes.insert(record.bib, record.race_time).is_some() {
+                return Err(RaceError::AlreadyFinished(record.bib));
+            }
+        }
+        for record in self.records.iter().filter(|record| !record.finish) {
+            if finishes
+                .get(&record.bib)
+                .is_some_and(|&finish| record.race_time >= finish)
+            {
+                return Err(RaceError::AlreadyFinished(record.bib));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bib, Race, RaceError, RaceResult};
+    use crate::error::ClockError;
+    use crate::time_source::MockTimeSource;
+
+    use std::time::Duration;
+
+    fn race(bibs: &[u32]) -> (Race<MockTimeSource>, MockTimeSource) {
+        let clock = MockTimeSource::new();
+        let mut race = Race::with_time_source(clock.clone());
+        for &bib in bibs {
+            race.add_competitor(bib.into(), format!("Runner {bib}"))
+                .unwrap();
+        }
+        (race, clock)
+    }
+
+    #[test]
+    fn test_shared_start() {
+        let (mut race, clock) = race(&[1, 2, 3, 4]);
+        assert_eq!(
+            race.add_competitor(Bib::from(2), "Twin"),
+            Err(RaceError::DuplicateBib(2.into()))
+        );
+        assert_eq!(
+            race.record_split(1.into()).unwrap_err(),
+            RaceError::Clock(ClockError::NotRunning)
+        );
+        race.start();
+
+        clock.advance(Duration::from_mins(1));
+        race.record_split(1.into()).unwrap();
+        clock.advance(Duration::from_secs(5));
+        race.record_split(2.into()).unwrap();
+        clock.advance(Duration::from_secs(55));
+        let finish = race.record_finish(1.into()).unwrap();
+        assert_eq!(finish.lap_number(), 2);
+        assert_eq!(finish.split_time(), Duration::from_mins(1));
+        assert_eq!(finish.total_time(), Duration::from_mins(2));
+        assert_eq!(
+            race.record_split(1.into()).unwrap_err(),
+            RaceError::AlreadyFinished(1.into())
+        );
+        assert_eq!(
+            race.record_finish(9.into()).unwrap_err(),
+            RaceError::UnknownBib(9.into())
+        );
+        clock.advance(Duration::from_secs(5));
+        race.record_finish(2.into()).unwrap();
+        race.record_finish(3.into()).unwrap();
+        clock.advance(Duration::from_secs(5));
+        race.record_finish(4.into()).unwrap();
+
+        let results = race.results();
+        let positions: Vec<_> = results.iter().map(RaceResult::position).collect();
+        assert_eq!(positions, [Some(1), Some(2), Some(2), Some(4)]);
+        assert_eq!(results[0].bib(), Bib::from(1));
+        assert_eq!(results[2].gap(), Some(Duration::from_secs(5)));
+        assert_eq!(results[3].gap(), Some(Duration::from_secs(10)));
+        assert_eq!(results[1].splits()[1].split_time(), Duration::from_mins(1));
+    }
+
+    #[test]
+    fn test_interval_starts_and_corrections() {
+        let (mut race, clock) = race(&[10, 20, 30]);
+        race.set_interval_starts(Duration::from_secs(30)).unwrap();
+        assert_eq!(race.start_offset(30.into()), Some(Duration::from_mins(1)));
+        race.start();
+
+        clock.advance(Duration::from_secs(45));
+        assert_eq!(
+            race.record_split(30.into()).unwrap_err(),
+            RaceError::NotStarted(30.into())
+        );
+        clock.advance(Duration::from_secs(75));
+        race.record_finish(20.into()).unwrap();
+        clock.advance(Duration::from_secs(10));
+        race.record_split(30.into()).unwrap();
+        assert_eq!(
+            race.record_finish(20.into()).unwrap_err(),
+            RaceError::AlreadyFinished(20.into())
+        );
+        race.record_finish(10.into()).unwrap();
+
+        assert_eq!(
+            race.correct_bib(1, 20.into()),
+            Err(RaceError::AlreadyFinished(20.into()))
+        );
+        assert_eq!(
+            race.correct_bib(2, 20.into()),
+            Err(RaceError::AlreadyFinished(20.into()))
+        );
+        assert_eq!(race.records()[2].bib(), Bib::from(10));
+        assert_eq!(
+            race.correct_bib(7, 10.into()),
+            Err(RaceError::UnknownRecord(7))
+        );
+
+        let results = race.results();
+        assert_eq!(results[0].bib(), Bib::from(20));
+        assert_eq!(results[0].time(), Some(Duration::from_secs(90)));
+        assert_eq!(results[1].bib(), Bib::from(10));
+        assert_eq!(results[1].gap(), Some(Duration::from_secs(40)));
+        assert_eq!(results[2].position(), None);
+        assert_eq!(results[2].splits()[0].total_time(), Duration::from_secs(70));
+
+        race.remove_record(2).unwrap();
+        race.correct_bib(0, 10.into()).unwrap();
+        assert_eq!(race.finish_time(10.into()), Some(Duration::from_mins(2)));
+        assert_eq!(race.finish_time(20.into()), None);
+    }
+}
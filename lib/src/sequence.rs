@@ -0,0 +1,398 @@
+//! A module that defines sequences of timed phases, such as interval-training sessions
+//!
+//! A [`Sequence`] runs a list of [`Phase`]s back to back: a warmup, a number of rounds of work
+//! and rest, and a cooldown, for example. The whole session is measured by a single [`Timer`], so
+//! moving from one phase to the next never loses or gains time.
+
+use std::time::Duration;
+
+use crate::time_source::{SystemTimeSource, TimeSource};
+use crate::timer::{Timer, TimerState};
+
+/// A labelled step of a [`Sequence`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Phase {
+    /// The label shown while the phase runs.
+    label: String,
+    /// How long the phase lasts.
+    duration: Duration,
+    /// The round the phase belongs to, if it is part of one.
+    round: Option<u32>,
+}
+
+impl Phase {
+    /// Creates a new [`Phase`] that is not part of a round.
+    #[must_use]
+    pub fn new(label: impl Into<String>, duration: Duration) -> Self {
+        Self {
+            label: label.into(),
+            duration,
+            round: None,
+        }
+    }
+
+    /// Returns this phase as part of the given round, counted from 1.
+    #[must_use]
+    pub fn in_round(mut self, round: u32) -> Self {
+        self.round = Some(round);
+        self
+    }
+
+    /// Returns the phases of an interval-training session: a warmup, `rounds` rounds of work
+    /// followed by rest, and a cooldown. Phases with a zero duration are left out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use clock::sequence::Phase;
+    ///
+    /// // A Tabata session: 8 rounds of 20 seconds of work and 10 seconds of rest.
+    /// let phases = Phase::interval_training(
+    ///     Duration::ZERO,
+    ///     Duration::from_secs(20),
+    ///     Duration::from_secs(10),
+    ///     8,
+    ///     Duration::ZERO,
+    /// );
+    ///
+    /// assert_eq!(phases.len(), 16);
+    /// assert_eq!(phases[3].label(), "Rest");
+    /// assert_eq!(phases[3].round(), Some(2));
+    /// ```
+    #[must_use]
+    pub fn interval_training(
+        warmup: Duration,
+        work: Duration,
+        rest: Duration,
+        rounds: u32,
+        cooldown: Duration,
+    ) -> Vec<Phase> {
+        let mut phases = vec![Phase::new("Warmup", warmup)];
+        if !work.is_zero() || !rest.is_zero() {
+            for round in 1..=rounds {
+                phases.push(Phase::new("Work", work).in_round(round));
+                phases.push(Phase::new("Rest", rest).in_round(round));
+            }
+        }
+        phases.push(Phase::new("Cooldown", cooldown));
+        phases.retain(|phase| !phase.duration.is_zero());
+        phases
+    }
+
+    /// Returns the label shown while the phase runs.
+    #[must_use]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns how long the phase lasts.
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns the round the phase belongs to, counted from 1, or `None` if it is not part of one.
+    #[must_use]
+    pub fn round(&self) -> Option<u32> {
+        self.round
+    }
+}
+
+/// A list of [`Phase`]s that run one after the other, moving on to the next phase as soon as the
+/// current one runs out.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use clock::sequence::{Phase, Sequence};
+/// use clock::time_source::MockTimeSource;
+///
+/// let phases = Phase::interval_training(
+///     Duration::from_secs(60),
+///     Duration::from_secs(40),
+///     Duration::from_secs(20),
+///     5,
+///     Duration::from_secs(60),
+/// );
+/// let clock = MockTimeSource::new();
+/// let mut session = Sequence::with_time_source(phases, clock.clone());
+///
+/// session.start();
+/// clock.advance(Duration::from_secs(60 + 60 + 50));
+///
+/// let phase = session.current_phase().unwrap();
+/// assert_eq!(phase.label(), "Rest");
+/// assert_eq!(phase.round(), Some(2));
+/// assert_eq!(session.phase_remaining(), Duration::from_secs(10));
+/// assert_eq!(session.remaining(), Duration::from_secs(3 * 60 + 60 + 10));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sequence<T: TimeSource = SystemTimeSource> {
+    /// The phases of the sequence, in order.
+    phases: Vec<Phase>,
+    /// The timer that measures the whole session, whose duration is the total length of the
+    /// phases adjusted for skipped and repeated time.
+    timer: Timer<T>,
+    /// The time skipped by [`Sequence::skip_phase`].
+    skipped: Duration,
+    /// The time repeated by [`Sequence::repeat_phase`].
+    repeated: Duration,
+}
+
+impl Sequence {
+    /// Creates a new [`Sequence`] of `phases` that reads the system clock.
+    #[must_use]
+    pub fn new(phases: Vec<Phase>) -> Self {
+        Self::with_time_source(phases, SystemTimeSource)
+    }
+}
+
+impl<T: TimeSource> Sequence<T> {
+    /// Creates a new [`Sequence`] of `phases` that reads the current time from `time_source`.
+    #[must_use]
+    pub fn with_time_source(phases: Vec<Phase>, time_source: T) -> Self {
+        let total = phases
+            .iter()
+            .map(Phase::duration)
+            .fold(Duration::ZERO, Duration::saturating_add);
+        Self {
+            phases,
+            timer: Timer::with_time_source(total, time_source),
+            skipped: Duration::ZERO,
+            repeated: Duration::ZERO,
+        }
+    }
+
+    /// Starts or resumes the sequence.
+    pub fn start(&mut self) {
+        self.timer.start();
+    }
+
+    /// Pauses the sequence.
+    pub fn stop(&mut self) {
+        self.timer.stop();
+    }
+
+    /// Stops the sequence and moves it back to the start of its first phase.
+    pub fn reset(&mut self) {
+        self.timer.reset();
+        self.timer.set_duration(self.total_duration());
+        self.skipped = Duration::ZERO;
+        self.repeated = Duration::ZERO;
+    }
+
+    /// Returns the state of the whole sequence, which is [`TimerState::Expired`] once its last
+    /// phase ran out.
+    #[must_use]
+    pub fn state(&self) -> TimerState {
+        self.timer.state()
+    }
+
+    /// Returns the phases of the sequence, in order.
+    #[must_use]
+    pub fn phases(&self) -> &[Phase] {
+        &self.phases
+    }
+
+    /// Returns the index of the current phase in [`Sequence::phases`], or `None` once the sequence
+    /// is over.
+    #[must_use]
+    pub fn phase_index(&self) -> Option<usize> {
+        self.locate().map(|(index, _, _)| index)
+    }
+
+    /// Returns the current phase, or `None` once the sequence is over.
+    #[must_use]
+    pub fn current_phase(&self) -> Option<&Phase> {
+        self.phase_index().map(|index| &self.phases[index])
+    }
+
+    /// Returns the round of the current phase, or `None` if it is not part of a round or the
+    /// sequence is over.
+    #[must_use]
+    pub fn round(&self) -> Option<u32> {
+        self.current_phase().and_then(Phase::round)
+    }
+
+    /// Returns the number of rounds in the sequence.
+    #[must_use]
+    pub fn rounds(&self) -> u32 {
+        self.phases
+            .iter()
+            .filter_map(Phase::round)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Returns the time left in the current phase.
+    #[must_use]
+    pub fn phase_remaining(&self) -> Duration {
+        self.locate().map_or(Duration::ZERO, |(_, _, end)| {
+            end.saturating_sub(self.position())
+        })
+    }
+
+    /// Returns the time left in the whole sequence, including skipped and repeated phases.
+    #[must_use]
+    pub fn remaining(&self) -> Duration {
+        self.timer.remaining()
+    }
+
+    /// Moves on to the next phase right away. Does nothing once the sequence is over.
+    pub fn skip_phase(&mut self) {
+        if let Some((_, _, end)) = self.locate() {
+            let skipped = end.saturating_sub(self.position());
+            self.skipped = self.skipped.saturating_add(skipped);
+            self.timer
+                .set_duration(self.timer.duration().saturating_sub(skipped));
+        }
+    }
+
+    /// Starts the current phase over, or the last phase once the sequence is over.
+    pub fn repeat_phase(&mut self) {
+        let start = match self.locate() {
+            Some((_, start, _)) => start,
+            None => match self.phases.last() {
+                Some(last) => self.total_duration().saturating_sub(last.duration),
+                None => return,
+            },
+        };
+        let repeated = self.position().saturating_sub(start);
+        self.repeated = self.repeated.saturating_add(repeated);
+        self.timer
+            .set_duration(self.timer.duration().saturating_add(repeated));
+    }
+
+    /// Returns the total length of the phases.
+    fn total_duration(&self) -> Duration {
+        self.phases
+            .iter()
+            .map(Phase::duration)
+            .fold(Duration::ZERO, Duration::saturating_add)
+    }
+
+    /// Returns how far into the phases the sequence is.
+    fn position(&self) -> Duration {
+        self.timer
+            .elapsed()
+            .saturating_add(self.skipped)
+            .saturating_sub(self.repeated)
+    }
+
+    /// Returns the index, start and end of the current phase, or `None` once the sequence is over.
+    fn locate(&self) -> Option<(usize, Duration, Duration)> {
+        let position = self.position();
+        let mut start = Duration::ZERO;
+        for (index, phase) in self.phases.iter().enumerate() {
+            let end = start.saturating_add(phase.duration);
+            if position < end {
+                return Some((index, start, end));
+            }
+            start = end;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Phase, Sequence};
+    use crate::time_source::MockTimeSource;
+    use crate::timer::TimerState;
+
+    use std::time::Duration;
+
+    fn mock_session() -> (Sequence<MockTimeSource>, MockTimeSource) {
+        let phases = Phase::interval_training(
+            Duration::from_secs(30),
+            Duration::from_secs(20),
+            Duration::from_secs(10),
+            3,
+            Duration::from_secs(30),
+        );
+        let clock = MockTimeSource::new();
+        (Sequence::with_time_source(phases, clock.clone()), clock)
+    }
+
+    fn label<T: crate::time_source::TimeSource>(session: &Sequence<T>) -> Option<&str> {
+        session.current_phase().map(Phase::label)
+    }
+
+    #[test]
+    fn test_advances_through_phases() {
+        let (mut session, clock) = mock_session();
+        assert_eq!(session.rounds(), 3);
+        assert_eq!(label(&session), Some("Warmup"));
+        assert_eq!(session.remaining(), Duration::from_secs(150));
+
+        session.start();
+        clock.advance(Duration::from_secs(35));
+        assert_eq!(label(&session), Some("Work"));
+        assert_eq!(session.round(), Some(1));
+
+        session.stop();
+        clock.advance(Duration::from_mins(5));
+        session.start();
+        clock.advance(Duration::from_secs(80));
+        assert_eq!(label(&session), Some("Rest"));
+        assert_eq!(session.round(), Some(3));
+        assert_eq!(session.phase_remaining(), Duration::from_secs(5));
+
+        clock.advance(Duration::from_mins(1));
+        assert_eq!(session.state(), TimerState::Expired);
+        assert_eq!(session.current_phase(), None);
+        assert_eq!(session.remaining(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_skip_and_repeat() {
+        let (mut session, clock) = mock_session();
+        session.start();
+        clock.advance(Duration::from_secs(10));
+
+        session.skip_phase();
+        assert_eq!(label(&session), Some("Work"));
+        assert_eq!(session.phase_remaining(), Duration::from_secs(20));
+        assert_eq!(session.remaining(), Duration::from_mins(2));
+
+        clock.advance(Duration::from_secs(15));
+        session.repeat_phase();
+        assert_eq!(session.phase_remaining(), Duration::from_secs(20));
+        assert_eq!(session.remaining(), Duration::from_mins(2));
+
+        clock.advance(Duration::from_secs(200));
+        assert_eq!(session.state(), TimerState::Expired);
+        session.repeat_phase();
+        assert_eq!(session.state(), TimerState::Running);
+        assert_eq!(label(&session), Some("Cooldown"));
+        assert_eq!(session.phase_remaining(), Duration::from_secs(30));
+
+        session.reset();
+        assert_eq!(label(&session), Some("Warmup"));
+        assert_eq!(session.remaining(), Duration::from_secs(150));
+    }
+
+    #[test]
+    fn test_huge_phases_saturate() {
+        let phases = Phase::interval_training(
+            Duration::MAX,
+            Duration::ZERO,
+            Duration::ZERO,
+            u32::MAX,
+            Duration::from_secs(1),
+        );
+        assert_eq!(phases.len(), 2);
+
+        let clock = MockTimeSource::new();
+        let mut session = Sequence::with_time_source(phases, clock.clone());
+        assert_eq!(session.remaining(), Duration::MAX);
+        session.start();
+        clock.advance(Duration::from_secs(5));
+        session.repeat_phase();
+        assert_eq!(label(&session), Some("Warmup"));
+        session.skip_phase();
+        session.repeat_phase();
+    }
+}
@@ -0,0 +1,191 @@
+//! A module that defines snapshots of timers and stopwatches, used to persist them across restarts
+//!
+//! A snapshot captures everything needed to bring a [`Timer`](crate::timer::Timer), a
+//! [`TimerSet`](crate::timer_set::TimerSet) or a [`Stopwatch`](crate::stopwatch::Stopwatch) back, along with the wall-clock time at which it was
+//! taken. The monotonic clock does not survive a restart, so a timer or stopwatch that was running
+//! when the snapshot was taken is restored with the wall-clock time that passed since then added to
+//! its elapsed time.
+//!
+//! With the `serde` feature enabled, snapshots implement `Serialize` and `Deserialize`.
+
+use std::time::{Duration, SystemTime};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::stopwatch::{LapTime, Segment};
+use crate::timer::{CompletionPolicy, TimerState, Warning};
+use crate::timer_set::TimerId;
+
+/// A snapshot of a [`Timer`](crate::timer::Timer), see
+/// [`Timer::snapshot`](crate::timer::Timer::snapshot).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimerSnapshot {
+    /// The duration of the timer.
+    pub(crate) duration: Duration,
+    /// The time the timer had run, including any time past its duration.
+    pub(crate) elapsed: Duration,
+    /// The state the timer was last moved to.
+    pub(crate) state: TimerState,
+    /// What the timer does once it runs out of time.
+    pub(crate) completion_policy: CompletionPolicy,
+    /// The runs folded into the stored state of the timer.
+    pub(crate) completed_runs: u32,
+    /// Whether the expiry the timer was in was already counted.
+    pub(crate) expiry_counted: bool,
+    /// The runs completed before the timer was last reset.
+    pub(crate) past_completions: u32,
+    /// The warning thresholds of the timer and whether they were reported.
+    pub(crate) warnings: Vec<Warning>,
+    /// The wall-clock time the timer was last started or resumed.
+    pub(crate) started_at: Option<SystemTime>,
+    /// The wall-clock time the snapshot was taken.
+    pub(crate) saved_at: SystemTime,
+}
+
+impl TimerSnapshot {
+    /// Returns the wall-clock time the snapshot was taken.
+    #[must_use]
+    pub fn saved_at(&self) -> SystemTime {
+        self.saved_at
+    }
+
+    /// Returns the state the timer was last moved to when the snapshot was taken, which is never
+    /// [`TimerState::Expired`].
+    #[must_use]
+    pub fn state(&self) -> TimerState {
+        self.state
+    }
+}
+
+/// A snapshot of a [`Stopwatch`](crate::stopwatch::Stopwatch), see
+/// [`Stopwatch::snapshot`](crate::stopwatch::Stopwatch::snapshot).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StopwatchSnapshot {
+    /// Whether the stopwatch was running.
+    pub(crate) running: bool,
+    /// The elapsed time of the stopwatch.
+    pub(crate) elapsed: Duration,
+    /// The wall-clock time the stopwatch was first started.
+    pub(crate) started_at: Option<SystemTime>,
+    /// The times the stopwatch ran, the last one up to when the snapshot was taken if it was
+    /// running.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) segments: Vec<Segment>,
+    /// The laps recorded by the stopwatch.
+    pub(crate) lap_times: Vec<LapTime>,
+    /// The time that was left until a delayed start, if the stopwatch was counting down.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) countdown: Option<Duration>,
+    /// The time the stopwatch spent stopped since it was first started, or `None` if the snapshot
+    /// was taken before it was recorded.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) paused: Option<Duration>,
+    /// The wall-clock time the snapshot was taken.
+    pub(crate) saved_at: SystemTime,
+}
+
+impl StopwatchSnapshot {
+    /// Returns the wall-clock time the snapshot was taken.
+    #[must_use]
+    pub fn saved_at(&self) -> SystemTime {
+        self.saved_at
+    }
+
+    /// Returns whether the stopwatch was running when the snapshot was taken.
+    #[must_use]
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+}
+
+/// A snapshot of a [`TimerSet`](crate::timer_set::TimerSet), see
+/// [`TimerSet::snapshot`](crate::timer_set::TimerSet::snapshot).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimerSetSnapshot {
+    /// The timers of the set, ordered by id.
+    pub(crate) entries: Vec<TimerSetEntrySnapshot>,
+    /// The id handed to the next timer that is added.
+    pub(crate) next_id: u64,
+}
+
+impl TimerSetSnapshot {
+    /// Returns the number of timers in the snapshot.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the snapshot has no timers.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// A snapshot of a timer in a [`TimerSet`](crate::timer_set::TimerSet).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(crate) struct TimerSetEntrySnapshot {
+    /// The id of the timer.
+    pub(crate) id: TimerId,
+    /// The label shown for the timer.
+    pub(crate) label: String,
+    /// The timer itself.
+    pub(crate) timer: TimerSnapshot,
+    /// The completions of the timer that were already returned by
+    /// [`TimerSet::take_expired`](crate::timer_set::TimerSet::take_expired).
+    pub(crate) reported_completions: u32,
+}
+
+/// Returns the wall-clock time that passed between `saved_at` and `now`, or zero if the wall clock
+/// is now before `saved_at`.
+pub(crate) fn time_since(saved_at: SystemTime, now: SystemTime) -> Duration {
+    now.duration_since(saved_at).unwrap_or_default()
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use crate::stopwatch::Stopwatch;
+    use crate::time_source::MockTimeSource;
+    use crate::timer::{Timer, WarningThreshold};
+
+    use std::time::Duration;
+
+    #[test]
+    fn test_serde_round_trip() {
+        let clock = MockTimeSource::new();
+        let mut timer = Timer::with_time_source(Duration::from_mins(5), clock.clone());
+        timer.add_warning(WarningThreshold::PercentRemaining(10));
+        timer.start();
+        let mut stopwatch = Stopwatch::with_time_source(clock.clone());
+        stopwatch.start();
+        clock.advance(Duration::from_secs(3));
+        stopwatch.add_lap();
+
+        let snapshot = timer.snapshot();
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert_eq!(
+            serde_json::from_str::<super::TimerSnapshot>(&json).unwrap(),
+            snapshot
+        );
+
+        let snapshot = stopwatch.snapshot();
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert_eq!(
+            serde_json::from_str::<super::StopwatchSnapshot>(&json).unwrap(),
+            snapshot
+        );
+
+        // Snapshots saved before segments were recorded still restore with their elapsed time.
+        let mut json = serde_json::to_value(&snapshot).unwrap();
+        json.as_object_mut().unwrap().remove("segments");
+        let legacy = serde_json::from_value::<super::StopwatchSnapshot>(json).unwrap();
+        let restored = Stopwatch::restore_with_time_source(legacy, clock.clone());
+        assert_eq!(restored.elapsed_time(), Duration::from_secs(3));
+        assert_eq!(restored.segments().len(), 1);
+    }
+}
@@ -2,15 +2,36 @@
 //! other events. It allows you to start, stop, and reset the stopwatch, as
 //! well as record lap times.
 
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
+
+use chrono::{DateTime, FixedOffset, Local, Offset, SecondsFormat, TimeZone};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::duration::SignedDuration;
+use crate::error::ClockError;
+use crate::lap_statistics::LapStatistics;
+use crate::snapshot::{self, StopwatchSnapshot};
+use crate::time_source::{SystemTimeSource, TimeSource};
 
 /// A struct representing a lap time, which includes the lap number and the time it took to complete the lap.
-#[repr(C)]
-#[derive(Debug, Clone, Copy)]
+///
+/// A lap can also carry a short label, e.g. "Uphill", and a longer free-form note. Laps recorded by
+/// a [`Stopwatch`] also carry the time of day they were taken, so they can be lined up with logs
+/// from other systems.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct LapTime {
     lap_number: usize,
     split_time: Duration,
     total_time: Duration,
+    #[cfg_attr(feature = "serde", serde(default))]
+    label: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    note: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    recorded_at: Option<DateTime<FixedOffset>>,
 }
 
 impl LapTime {
@@ -29,9 +50,20 @@ impl LapTime {
             lap_number,
             split_time,
             total_time,
+            label: None,
+            note: None,
+            recorded_at: None,
         }
     }
 
+    /// Returns the lap with `recorded_at` as the time of day it was taken, keeping the UTC offset
+    /// of `recorded_at`.
+    #[must_use]
+    pub fn with_recorded_at<Tz: TimeZone>(mut self, recorded_at: &DateTime<Tz>) -> Self {
+        self.recorded_at = Some(recorded_at.with_timezone(&recorded_at.offset().fix()));
+        self
+    }
+
     /// A `u32` representing the lap number.
     #[must_use]
     pub fn lap_number(&self) -> usize {
@@ -49,161 +81,550 @@ impl LapTime {
     pub fn total_time(&self) -> Duration {
         self.total_time
     }
+
+    /// The label of the lap, if it has one.
+    #[must_use]
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Sets or, with `None`, removes the label of the lap.
+    pub fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    /// The note attached to the lap, if it has one.
+    #[must_use]
+    pub fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+
+    /// Sets or, with `None`, removes the note attached to the lap.
+    pub fn set_note(&mut self, note: Option<String>) {
+        self.note = note;
+    }
+
+    /// The time of day the lap was taken, with the UTC offset of the local time zone at that
+    /// moment, or `None` if it is not known, e.g. for a lap of a plan.
+    ///
+    /// Use [`DateTime::with_timezone`] to show it in another time zone.
+    #[must_use]
+    pub fn recorded_at(&self) -> Option<DateTime<FixedOffset>> {
+        self.recorded_at
+    }
+
+    /// The time of day the lap was taken in ISO 8601 format, e.g. `2024-03-01T17:30:05.250+01:00`,
+    /// or `None` if it is not known.
+    #[must_use]
+    pub fn recorded_at_iso8601(&self) -> Option<String> {
+        self.recorded_at
+            .map(|recorded_at| recorded_at.to_rfc3339_opts(SecondsFormat::AutoSi, false))
+    }
+}
+
+/// A span of time during which a [`Stopwatch`] was running, see [`Stopwatch::segments`].
+///
+/// The start and stop are wall-clock timestamps meant for display, while the duration is measured
+/// on the monotonic clock like the elapsed time of the stopwatch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Segment {
+    started_at: SystemTime,
+    stopped_at: Option<SystemTime>,
+    duration: Duration,
+}
+
+impl Segment {
+    /// The wall-clock time when the stopwatch was started or resumed.
+    #[must_use]
+    pub fn started_at(&self) -> SystemTime {
+        self.started_at
+    }
+
+    /// The wall-clock time when the stopwatch was stopped, or `None` if it is still running.
+    #[must_use]
+    pub fn stopped_at(&self) -> Option<SystemTime> {
+        self.stopped_at
+    }
+
+    /// The time the stopwatch ran during the segment, so far if it is still running.
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns true if the stopwatch is still running in this segment.
+    #[must_use]
+    pub fn is_open(&self) -> bool {
+        self.stopped_at.is_none()
+    }
+
+    /// Returns the segment stopped at `stopped_at` if it is still open.
+    pub(crate) fn closed(mut self, stopped_at: SystemTime) -> Self {
+        self.stopped_at.get_or_insert(stopped_at);
+        self
+    }
 }
 
 /// A stopwatch that can be used to measure elapsed time and lap times.
 ///
 /// The `Stopwatch` struct can be used to measure elapsed time as well as lap times. It can be started, paused,
 /// and reset as needed, and it keeps track of all lap times that have been recorded.
+///
+/// The stopwatch reads the current time from a [`TimeSource`], which is the system clock unless
+/// another source is given with [`Stopwatch::with_time_source`]. Elapsed time is measured on the
+/// monotonic clock, so changes to the wall clock never affect a running session.
+///
+/// Every time the stopwatch runs between a start and a stop is recorded as a [`Segment`], and the
+/// elapsed time is the sum of the segments.
 #[repr(C)]
 #[derive(Debug)]
-pub struct Stopwatch {
-    /// A boolean that is true if the stopwatch is currently running and false if it is not.
-    running: bool,
-    /// An `SystemTime` representing the time when the stopwatch was last started or reset.
-    total_start_time: SystemTime,
-    /// An `SystemTime` representing the time when the last lap was started.
-    split_start_time: SystemTime,
-    /// A `Duration` representing the elapsed time since the stopwatch was last started or reset.
-    elapsed_time: Duration,
-    /// An `Option<SystemTime>` representing the time when the stopwatch was last paused or None if it has never been paused.
-    paused_time: Option<SystemTime>,
+pub struct Stopwatch<T: TimeSource = SystemTimeSource> {
+    /// A `Vec<Segment>` representing the times the stopwatch ran since it was created or reset. The
+    /// stopwatch is running if the last segment is open.
+    segments: Vec<Segment>,
+    /// An `Instant` representing the time the open segment is measured from. The duration stored
+    /// in the open segment is the time it ran before then, e.g. before the stopwatch was restored.
+    start_time: Instant,
     /// A `Vec<LapTime>` representing the lap times for the stopwatch.
     lap_times: Vec<LapTime>,
+    /// An `Option<Instant>` representing the time a delayed start counts down to, see
+    /// [`Stopwatch::start_after`]. Once that time has passed the stopwatch counts as running from it.
+    countdown_end: Option<Instant>,
+    /// A `Duration` representing the time the stopwatch spent stopped before the current run or
+    /// pause, see [`Stopwatch::paused_time`].
+    paused_time: Duration,
+    /// An `Option<Instant>` representing the time the current pause is measured from, or `None` if
+    /// the stopwatch is running or was never started.
+    stop_time: Option<Instant>,
+    /// The `TimeSource` the stopwatch reads the current time from.
+    time_source: T,
 }
 
 impl Stopwatch {
     /// A constructor that creates a new `Stopwatch` with default values.
     #[must_use]
     pub fn new() -> Self {
+        Self::with_time_source(SystemTimeSource)
+    }
+
+    /// A constructor that creates a `Stopwatch` that reads the system clock from a snapshot taken
+    /// with [`Stopwatch::snapshot`], see [`Stopwatch::restore_with_time_source`].
+    #[must_use]
+    pub fn restore(snapshot: StopwatchSnapshot) -> Self {
+        Self::restore_with_time_source(snapshot, SystemTimeSource)
+    }
+}
+
+impl<T: TimeSource> Stopwatch<T> {
+    /// A constructor that creates a new `Stopwatch` that reads the current time from `time_source`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use clock::stopwatch::Stopwatch;
+    /// use clock::time_source::MockTimeSource;
+    ///
+    /// let clock = MockTimeSource::new();
+    /// let mut stopwatch = Stopwatch::with_time_source(clock.clone());
+    ///
+    /// stopwatch.start();
+    /// clock.advance(Duration::from_secs(2));
+    ///
+    /// assert_eq!(stopwatch.elapsed_time(), Duration::from_secs(2));
+    /// ```
+    #[must_use]
+    pub fn with_time_source(time_source: T) -> Self {
         Self {
-            running: false,
-            total_start_time: SystemTime::now(),
-            split_start_time: SystemTime::now(),
-            elapsed_time: Duration::from_secs(0),
-            paused_time: None,
+            segments: Vec::new(),
+            start_time: time_source.now(),
             lap_times: Vec::new(),
+            countdown_end: None,
+            paused_time: Duration::ZERO,
+            stop_time: None,
+            time_source,
         }
     }
 
+    /// Returns the `TimeSource` the stopwatch reads the current time from.
+    #[must_use]
+    pub fn time_source(&self) -> &T {
+        &self.time_source
+    }
+
     /// Starts the stopwatch. If the stopwatch is already running, this method does nothing.
     pub fn start(&mut self) {
-        if !self.running {
-            if let Some(paused_time) = self.paused_time {
-                self.total_start_time +=
-                    if let Ok(duration) = SystemTime::now().duration_since(paused_time) {
-                        duration
-                    } else {
-                        self.reset();
-                        return;
-                    };
-                self.split_start_time +=
-                    if let Ok(duration) = SystemTime::now().duration_since(paused_time) {
-                        duration
-                    } else {
-                        self.reset();
-                        return;
-                    };
-            } else {
-                self.total_start_time = SystemTime::now();
-                self.split_start_time = SystemTime::now();
-            }
-            self.running = true;
-            self.paused_time = None;
+        let _ = self.try_start();
+    }
+
+    /// Starts the stopwatch.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClockError::AlreadyRunning`] if the stopwatch is already running or counting down
+    /// to a delayed start.
+    pub fn try_start(&mut self) -> Result<(), ClockError> {
+        self.settle_countdown();
+        if self.running() || self.countdown_end.is_some() {
+            return Err(ClockError::AlreadyRunning);
+        }
+
+        self.open_segment(self.time_source.now());
+        Ok(())
+    }
+
+    /// Starts the stopwatch once `countdown` has passed, e.g. after a 3-2-1 countdown before a race.
+    /// If the stopwatch is already running or counting down, this method does nothing.
+    ///
+    /// See [`Stopwatch::try_start_after`].
+    pub fn start_after(&mut self, countdown: Duration) {
+        let _ = self.try_start_after(countdown);
+    }
+
+    /// Starts the stopwatch once `countdown` has passed, e.g. after a 3-2-1 countdown before a race.
+    ///
+    /// The zero of the stopwatch is the end of the countdown rather than the moment this method is
+    /// called, however late the stopwatch is next read. Until then the stopwatch is not running,
+    /// [`Stopwatch::countdown_remaining`] reports the time left and the countdown can be cancelled
+    /// with [`Stopwatch::cancel_countdown`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClockError::AlreadyRunning`] if the stopwatch is already running or counting down.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use clock::stopwatch::Stopwatch;
+    /// use clock::time_source::MockTimeSource;
+    ///
+    /// let clock = MockTimeSource::new();
+    /// let mut stopwatch = Stopwatch::with_time_source(clock.clone());
+    /// stopwatch.try_start_after(Duration::from_secs(3)).unwrap();
+    ///
+    /// clock.advance(Duration::from_secs(2));
+    /// assert!(!stopwatch.is_running());
+    /// assert_eq!(stopwatch.countdown_remaining(), Some(Duration::from_secs(1)));
+    ///
+    /// clock.advance(Duration::from_millis(1500));
+    /// assert!(stopwatch.is_running());
+    /// assert_eq!(stopwatch.countdown_remaining(), None);
+    /// assert_eq!(stopwatch.elapsed_time(), Duration::from_millis(500));
+    /// ```
+    pub fn try_start_after(&mut self, countdown: Duration) -> Result<(), ClockError> {
+        self.settle_countdown();
+        if self.running() || self.countdown_end.is_some() {
+            return Err(ClockError::AlreadyRunning);
+        }
+
+        let now = self.time_source.now();
+        self.countdown_end = Some(now.checked_add(countdown).unwrap_or(now));
+        Ok(())
+    }
+
+    /// Returns the time left until a delayed start, or `None` if the stopwatch is not counting
+    /// down.
+    #[must_use]
+    pub fn countdown_remaining(&self) -> Option<Duration> {
+        let remaining = self
+            .countdown_end?
+            .checked_duration_since(self.time_source.now())?;
+        (!remaining.is_zero()).then_some(remaining)
+    }
+
+    /// Returns true if the stopwatch is counting down to a delayed start.
+    #[must_use]
+    pub fn is_counting_down(&self) -> bool {
+        self.countdown_remaining().is_some()
+    }
+
+    /// Cancels a delayed start. Returns `false` if the stopwatch was not counting down, including
+    /// when the countdown has already ended and the stopwatch is running.
+    pub fn cancel_countdown(&mut self) -> bool {
+        self.settle_countdown();
+        self.countdown_end.take().is_some()
+    }
+
+    /// Moves the stopwatch from a countdown that has ended to running from the end of that
+    /// countdown.
+    fn settle_countdown(&mut self) {
+        let Some(countdown_end) = self.countdown_end else {
+            return;
+        };
+        let now = self.time_source.now();
+        if now < countdown_end {
+            return;
+        }
+
+        self.countdown_end = None;
+        self.open_segment(countdown_end);
+    }
+
+    /// Starts a segment at `start_time`, which must not be in the future.
+    fn open_segment(&mut self, start_time: Instant) {
+        if let Some(stop_time) = self.stop_time.take() {
+            self.paused_time += start_time.saturating_duration_since(stop_time);
+        }
+        self.start_time = start_time;
+        self.segments.push(Segment {
+            started_at: self.wall_clock_at(start_time),
+            stopped_at: None,
+            duration: Duration::ZERO,
+        });
+    }
+
+    /// Ends the open segment now, measuring it on the monotonic clock.
+    ///
+    /// Returns [`ClockError::ClockWentBackwards`] without ending it if the time source reports a
+    /// time before the segment was started, unless `saturate` is set, in which case only the time
+    /// it ran before then is counted.
+    fn close_segment(&mut self, saturate: bool) -> Result<(), ClockError> {
+        let now = self.time_source.now();
+        let running_time = match now.checked_duration_since(self.start_time) {
+            Some(running_time) => running_time,
+            None if saturate => Duration::ZERO,
+            None => return Err(ClockError::ClockWentBackwards),
+        };
+        let stopped_at = self.time_source.wall_clock();
+        if let Some(segment) = self.segments.last_mut().filter(|segment| segment.is_open()) {
+            segment.duration += running_time;
+            segment.stopped_at = Some(stopped_at);
         }
+        self.stop_time = Some(now);
+        Ok(())
+    }
+
+    /// Returns true if the last segment is open, not counting a countdown that has ended.
+    fn running(&self) -> bool {
+        self.segments.last().is_some_and(Segment::is_open)
+    }
+
+    /// Returns the time the stopwatch ran in all segments, not counting the time the open segment
+    /// ran since `start_time`.
+    fn stored_elapsed_time(&self) -> Duration {
+        self.segments.iter().map(Segment::duration).sum()
+    }
+
+    /// Returns the wall-clock time at `instant`, which must not be in the future.
+    fn wall_clock_at(&self, instant: Instant) -> SystemTime {
+        let wall_clock = self.time_source.wall_clock();
+        wall_clock
+            .checked_sub(self.time_source.now().saturating_duration_since(instant))
+            .unwrap_or(wall_clock)
+    }
+
+    /// Returns the time the stopwatch was last started or resumed, including at the end of a
+    /// countdown, or `None` if it is not running.
+    fn running_since(&self) -> Option<Instant> {
+        if self.running() {
+            return Some(self.start_time);
+        }
+        self.countdown_end
+            .filter(|&countdown_end| self.time_source.now() >= countdown_end)
     }
 
     /// Stops the stopwatch. If the stopwatch is not running, this method does nothing.
     pub fn stop(&mut self) {
-        if self.running {
-            self.paused_time = Some(SystemTime::now());
-            self.elapsed_time = if let Ok(duration) = self.total_start_time.elapsed() {
-                duration
-            } else {
-                self.reset();
-                return;
-            };
-            self.running = false;
+        self.settle_countdown();
+        if self.running() {
+            let _ = self.close_segment(true);
+        }
+    }
+
+    /// Stops the stopwatch.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClockError::NotRunning`] if the stopwatch is not running, or
+    /// [`ClockError::ClockWentBackwards`] if the time source reports a time before the stopwatch was
+    /// last started. The stopwatch is left running in both cases.
+    pub fn try_stop(&mut self) -> Result<(), ClockError> {
+        self.settle_countdown();
+        if !self.running() {
+            return Err(ClockError::NotRunning);
         }
+
+        self.close_segment(false)
     }
 
     /// Resets the stopwatch to its initial state.
     pub fn reset(&mut self) {
-        self.running = false;
-        self.paused_time = None;
-        self.total_start_time = SystemTime::now();
-        self.elapsed_time = Duration::from_secs(0);
+        self.segments.clear();
+        self.start_time = self.time_source.now();
         self.lap_times.clear();
+        self.countdown_end = None;
+        self.paused_time = Duration::ZERO;
+        self.stop_time = None;
     }
 
-    /// Adds a lap time to the stopwatch.
+    /// Adds a lap time to the stopwatch. If the stopwatch is not running, this method does nothing.
+    ///
+    /// The split time of the lap is measured from the previous lap, or from the start of the
+    /// stopwatch for the first lap.
     pub fn add_lap(&mut self) {
-        let now = SystemTime::now();
-        if self.running {
-            let lap_time = LapTime {
-                lap_number: self.lap_times.len() + 1,
-                split_time: if let Ok(duration) = now.duration_since(self.split_start_time) {
-                    duration
-                } else {
-                    self.reset();
-                    return;
-                },
-                total_time: if let Ok(duration) = self.total_start_time.elapsed() {
-                    duration
-                } else {
-                    self.reset();
-                    return;
-                },
-            };
-
-            self.lap_times.push(lap_time);
-            self.elapsed_time = if let Ok(duration) = self.total_start_time.elapsed() {
-                duration
-            } else {
-                self.reset();
-                return;
-            };
+        self.settle_countdown();
+        if self.running() {
+            self.push_lap(self.elapsed_time());
         }
+    }
 
-        self.split_start_time = now;
+    /// Adds a lap time to the stopwatch and returns it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClockError::NotRunning`] if the stopwatch is not running, or
+    /// [`ClockError::ClockWentBackwards`] if the time source reports a time before the stopwatch was
+    /// last started. No lap is recorded in either case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use clock::error::ClockError;
+    /// use clock::stopwatch::Stopwatch;
+    /// use clock::time_source::MockTimeSource;
+    ///
+    /// let clock = MockTimeSource::new();
+    /// let mut stopwatch = Stopwatch::with_time_source(clock.clone());
+    /// assert_eq!(stopwatch.try_add_lap().unwrap_err(), ClockError::NotRunning);
+    ///
+    /// stopwatch.start();
+    /// clock.advance(Duration::from_secs(3));
+    ///
+    /// let lap = stopwatch.try_add_lap().unwrap();
+    /// assert_eq!(lap.lap_number(), 1);
+    /// assert_eq!(lap.total_time(), Duration::from_secs(3));
+    /// ```
+    pub fn try_add_lap(&mut self) -> Result<LapTime, ClockError> {
+        self.settle_countdown();
+        if !self.running() {
+            return Err(ClockError::NotRunning);
+        }
+
+        let total_time = self.try_elapsed_time()?;
+        Ok(self.push_lap(total_time))
+    }
+
+    /// Records a lap that ends at `total_time` and returns it.
+    fn push_lap(&mut self, total_time: Duration) -> LapTime {
+        let previous_total_time = self
+            .lap_times
+            .last()
+            .map_or(Duration::ZERO, LapTime::total_time);
+        let lap_time = LapTime::new(
+            self.lap_times.len() + 1,
+            total_time.saturating_sub(previous_total_time),
+            total_time,
+        )
+        .with_recorded_at(&DateTime::<Local>::from(self.time_source.wall_clock()));
+
+        self.lap_times.push(lap_time.clone());
+        lap_time
     }
 
     /// Returns true if the stopwatch is currently running, and false otherwise.
     #[must_use]
     pub fn is_running(&self) -> bool {
-        self.running
+        self.running_since().is_some()
     }
 
     /// Returns the elapsed time since the stopwatch was last started or reset.
-    pub fn elapsed_time(&mut self) -> Duration {
-        let mut elapsed = if self.paused_time.is_none() {
-            Duration::from_secs(0)
-        } else if let Ok(duration) = self.total_start_time.elapsed() {
-            duration
-        } else {
-            self.reset();
-            return self.elapsed_time;
-        };
+    #[must_use]
+    pub fn elapsed_time(&self) -> Duration {
+        self.try_elapsed_time()
+            .unwrap_or_else(|_| self.stored_elapsed_time())
+    }
 
-        if self.is_running() {
-            elapsed = if let Ok(duration) = self.total_start_time.elapsed() {
-                duration
-            } else {
-                self.reset();
-                return self.elapsed_time;
-            };
+    /// Returns the elapsed time since the stopwatch was last started or reset.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClockError::ClockWentBackwards`] if the stopwatch is running and the time source
+    /// reports a time before the stopwatch was last started.
+    pub fn try_elapsed_time(&self) -> Result<Duration, ClockError> {
+        match self.running_since() {
+            Some(start_time) => self
+                .time_source
+                .now()
+                .checked_duration_since(start_time)
+                .map(|running_time| self.stored_elapsed_time() + running_time)
+                .ok_or(ClockError::ClockWentBackwards),
+            None => Ok(self.stored_elapsed_time()),
         }
+    }
 
-        if let Some(paused_time) = self.paused_time {
-            elapsed -= if let Ok(duration) = SystemTime::now().duration_since(paused_time) {
-                duration
-            } else {
-                self.reset();
-                return self.elapsed_time;
-            }
+    /// Returns the wall-clock time when the stopwatch was first started since it was created or
+    /// reset, or `None` if it has not been started.
+    ///
+    /// This is meant for display and persistence only; elapsed and lap times never depend on it.
+    #[must_use]
+    pub fn started_at(&self) -> Option<SystemTime> {
+        self.segments.first().map(Segment::started_at).or_else(|| {
+            self.running_since()
+                .map(|start_time| self.wall_clock_at(start_time))
+        })
+    }
+
+    /// Returns the times the stopwatch ran since it was created or reset, oldest first. If the
+    /// stopwatch is running, the last segment is open and its duration is the time it has run so
+    /// far.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use clock::stopwatch::Stopwatch;
+    /// use clock::time_source::MockTimeSource;
+    ///
+    /// let clock = MockTimeSource::new();
+    /// let mut stopwatch = Stopwatch::with_time_source(clock.clone());
+    /// stopwatch.start();
+    /// clock.advance(Duration::from_secs(5));
+    /// stopwatch.stop();
+    /// clock.advance(Duration::from_secs(60));
+    /// stopwatch.start();
+    /// clock.advance(Duration::from_secs(2));
+    ///
+    /// let segments = stopwatch.segments();
+    /// assert_eq!(segments.len(), 2);
+    /// assert_eq!(segments[0].duration(), Duration::from_secs(5));
+    /// assert!(segments[1].is_open());
+    /// assert_eq!(stopwatch.elapsed_time(), Duration::from_secs(7));
+    /// assert_eq!(stopwatch.paused_time(), Duration::from_secs(60));
+    /// ```
+    #[must_use]
+    pub fn segments(&self) -> Vec<Segment> {
+        let now = self.time_source.now();
+        let mut segments = self.segments.clone();
+        if let Some(segment) = segments.last_mut().filter(|segment| segment.is_open()) {
+            segment.duration += now.saturating_duration_since(self.start_time);
+        } else if let Some(start_time) = self.running_since() {
+            segments.push(Segment {
+                started_at: self.wall_clock_at(start_time),
+                stopped_at: None,
+                duration: now.saturating_duration_since(start_time),
+            });
         }
+        segments
+    }
 
-        elapsed
+    /// Returns the time the stopwatch spent stopped since it was first started, including the
+    /// current pause if it is stopped.
+    ///
+    /// Pauses are measured on the monotonic clock like the elapsed time, so changes to the wall
+    /// clock do not affect them. Only the part of a pause that spans a
+    /// [restore](Stopwatch::restore_with_time_source) is measured on the wall clock, since the
+    /// monotonic clock does not carry over from one process to the next.
+    #[must_use]
+    pub fn paused_time(&self) -> Duration {
+        let current_pause = self.stop_time.map_or(Duration::ZERO, |stop_time| {
+            self.running_since()
+                .unwrap_or_else(|| self.time_source.now())
+                .saturating_duration_since(stop_time)
+        });
+        self.paused_time + current_pause
     }
 
     /// Returns a slice of the lap times for the stopwatch.
@@ -211,6 +632,234 @@ impl Stopwatch {
     pub fn lap_times(&self) -> &[LapTime] {
         self.lap_times.as_slice()
     }
+
+    /// Returns statistics over the split times of the laps, such as the fastest lap and the mean.
+    #[must_use]
+    pub fn lap_statistics(&self) -> LapStatistics<'_> {
+        LapStatistics::new(&self.lap_times)
+    }
+
+    /// Returns the lap numbered `lap_number`, so its label and note can be edited, or `None` if
+    /// there is no such lap.
+    #[must_use]
+    pub fn lap_mut(&mut self, lap_number: usize) -> Option<&mut LapTime> {
+        self.lap_times.get_mut(lap_number.checked_sub(1)?)
+    }
+
+    /// Removes the lap numbered `lap_number`, e.g. one recorded by accident, and returns it.
+    ///
+    /// The time of the removed lap is merged into the lap that follows it, whose split time is then
+    /// measured from the lap before, and the later laps are renumbered. Returns `None` if there is
+    /// no such lap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use clock::stopwatch::Stopwatch;
+    /// use clock::time_source::MockTimeSource;
+    ///
+    /// let clock = MockTimeSource::new();
+    /// let mut stopwatch = Stopwatch::with_time_source(clock.clone());
+    /// stopwatch.start();
+    /// for seconds in [30, 2, 28] {
+    ///     clock.advance(Duration::from_secs(seconds));
+    ///     stopwatch.add_lap();
+    /// }
+    ///
+    /// stopwatch.remove_lap(2);
+    ///
+    /// let laps = stopwatch.lap_times();
+    /// assert_eq!(laps.len(), 2);
+    /// assert_eq!(laps[1].lap_number(), 2);
+    /// assert_eq!(laps[1].split_time(), Duration::from_secs(30));
+    /// ```
+    pub fn remove_lap(&mut self, lap_number: usize) -> Option<LapTime> {
+        let index = lap_number.checked_sub(1)?;
+        if index >= self.lap_times.len() {
+            return None;
+        }
+
+        let lap_time = self.lap_times.remove(index);
+        self.renumber_laps();
+        Some(lap_time)
+    }
+
+    /// Inserts a lap that ended at `total_time`, e.g. one that was missed while it happened, and
+    /// returns it.
+    ///
+    /// The lap is placed among the recorded laps by its total time, the split time of the lap that
+    /// follows it is shortened accordingly and the later laps are renumbered.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClockError::LapAfterElapsedTime`] if `total_time` is past the elapsed time of the
+    /// stopwatch, or [`ClockError::ClockWentBackwards`] if the time source reports a time before
+    /// the stopwatch was last started. No lap is recorded in either case.
+    pub fn insert_lap(&mut self, total_time: Duration) -> Result<LapTime, ClockError> {
+        if total_time > self.try_elapsed_time()? {
+            return Err(ClockError::LapAfterElapsedTime);
+        }
+
+        let index = self
+            .lap_times
+            .partition_point(|lap| lap.total_time <= total_time);
+        let mut lap_time = LapTime::new(0, Duration::ZERO, total_time);
+        if let Some(recorded_at) = self.wall_clock_at_elapsed(total_time) {
+            lap_time = lap_time.with_recorded_at(&DateTime::<Local>::from(recorded_at));
+        }
+        self.lap_times.insert(index, lap_time);
+        self.renumber_laps();
+        Ok(self.lap_times[index].clone())
+    }
+
+    /// Returns the wall-clock time at which the stopwatch had run for `elapsed`, going by the start
+    /// of the segment it was in then, or `None` if it has not run that long.
+    fn wall_clock_at_elapsed(&self, elapsed: Duration) -> Option<SystemTime> {
+        let mut before = Duration::ZERO;
+        for segment in self.segments() {
+            if elapsed <= before + segment.duration {
+                return segment
+                    .started_at
+                    .checked_add(elapsed.saturating_sub(before));
+            }
+            before += segment.duration;
+        }
+        None
+    }
+
+    /// Recomputes the number and split time of every lap from the order and total times of the
+    /// laps.
+    fn renumber_laps(&mut self) {
+        let mut previous_total_time = Duration::ZERO;
+        for (index, lap_time) in self.lap_times.iter_mut().enumerate() {
+            lap_time.lap_number = index + 1;
+            lap_time.split_time = lap_time.total_time.saturating_sub(previous_total_time);
+            previous_total_time = lap_time.total_time;
+        }
+    }
+
+    /// Returns a snapshot of the stopwatch that can be persisted and turned back into a stopwatch
+    /// with [`Stopwatch::restore_with_time_source`], e.g. after the process restarts.
+    #[must_use]
+    pub fn snapshot(&self) -> StopwatchSnapshot {
+        StopwatchSnapshot {
+            running: self.is_running(),
+            elapsed: self.elapsed_time(),
+            started_at: self.started_at(),
+            segments: self.segments(),
+            lap_times: self.lap_times.clone(),
+            countdown: self.countdown_remaining(),
+            paused: Some(self.paused_time()),
+            saved_at: self.time_source.wall_clock(),
+        }
+    }
+
+    /// A constructor that creates a `Stopwatch` that reads the current time from `time_source`
+    /// from a snapshot taken with [`Stopwatch::snapshot`].
+    ///
+    /// A stopwatch that was running when the snapshot was taken keeps running, with the wall-clock
+    /// time that passed since then counted as elapsed. A stopwatch that was counting down to a
+    /// delayed start keeps counting down, or runs from the end of the countdown if it has passed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use clock::stopwatch::Stopwatch;
+    /// use clock::time_source::MockTimeSource;
+    ///
+    /// let clock = MockTimeSource::new();
+    /// let mut stopwatch = Stopwatch::with_time_source(clock.clone());
+    /// stopwatch.start();
+    /// clock.advance(Duration::from_secs(5));
+    /// let snapshot = stopwatch.snapshot();
+    ///
+    /// clock.advance(Duration::from_secs(3));
+    ///
+    /// let stopwatch = Stopwatch::restore_with_time_source(snapshot, clock.clone());
+    /// assert!(stopwatch.is_running());
+    /// assert_eq!(stopwatch.elapsed_time(), Duration::from_secs(8));
+    /// ```
+    #[must_use]
+    pub fn restore_with_time_source(snapshot: StopwatchSnapshot, time_source: T) -> Self {
+        let time_since = snapshot::time_since(snapshot.saved_at, time_source.wall_clock());
+        let now = time_source.now();
+        let mut segments = snapshot.segments;
+        if segments.is_empty() && (snapshot.running || !snapshot.elapsed.is_zero()) {
+            // The snapshot was taken before segments were recorded, so the whole elapsed time is
+            // kept as a single segment.
+            segments.push(Segment {
+                started_at: snapshot.started_at.unwrap_or(snapshot.saved_at),
+                stopped_at: (!snapshot.running).then_some(snapshot.saved_at),
+                duration: snapshot.elapsed,
+            });
+        }
+
+        let mut paused_time = snapshot
+            .paused
+            .unwrap_or_else(|| wall_clock_pauses(&segments, snapshot.saved_at));
+        // The pause the stopwatch was in when the snapshot was taken goes on across the restore,
+        // which only the wall clock can measure.
+        let stopped = !segments.is_empty() && !snapshot.running;
+        let mut stop_time = None;
+        let mut countdown_end = None;
+        if let Some(segment) = segments.last_mut().filter(|segment| segment.is_open()) {
+            segment.duration += time_since;
+        } else if let Some(countdown) = snapshot.countdown {
+            match countdown.checked_sub(time_since) {
+                Some(remaining) if !remaining.is_zero() => {
+                    countdown_end = Some(now.checked_add(remaining).unwrap_or(now));
+                    if stopped {
+                        paused_time += time_since;
+                        stop_time = Some(now);
+                    }
+                }
+                _ => {
+                    if stopped {
+                        paused_time += countdown;
+                    }
+                    segments.push(Segment {
+                        started_at: snapshot
+                            .saved_at
+                            .checked_add(countdown)
+                            .unwrap_or(snapshot.saved_at),
+                        stopped_at: None,
+                        duration: time_since.saturating_sub(countdown),
+                    });
+                }
+            }
+        } else if stopped {
+            paused_time += time_since;
+            stop_time = Some(now);
+        }
+        Self {
+            segments,
+            start_time: now,
+            lap_times: snapshot.lap_times,
+            countdown_end,
+            paused_time,
+            stop_time,
+            time_source,
+        }
+    }
+}
+
+/// Returns the wall-clock time between `segments`, and from the end of the last one until
+/// `saved_at` if it is closed, for snapshots taken before pauses were recorded.
+fn wall_clock_pauses(segments: &[Segment], saved_at: SystemTime) -> Duration {
+    let pauses = segments.windows(2).map(|pair| {
+        pair[1]
+            .started_at
+            .duration_since(pair[0].stopped_at.unwrap_or(pair[0].started_at))
+            .unwrap_or_default()
+    });
+    let current_pause = segments
+        .last()
+        .and_then(|segment| segment.stopped_at)
+        .and_then(|stopped_at| saved_at.duration_since(stopped_at).ok())
+        .unwrap_or_default();
+    pauses.sum::<Duration>() + current_pause
 }
 
 impl Default for Stopwatch {
@@ -227,19 +876,45 @@ pub fn format_time(duration: Duration) -> String {
     format!("{:02}:{:02}.{:02}", secs / 60, secs % 60, millis / 10)
 }
 
+/// This function formats a `SignedDuration` like [`format_time`], prefixed with a minus sign when it is negative, e.g. "-00:42.00" for a timer that is 42 seconds overdue.
+#[must_use]
+pub fn format_signed_time(duration: SignedDuration) -> String {
+    let time = format_time(duration.magnitude());
+    if duration.is_negative() {
+        format!("-{time}")
+    } else {
+        time
+    }
+}
+
+/// This function formats a `SignedDuration` as a delta against a reference, always with a sign and with unpadded minutes, e.g. "+0:01.23" for 1.23 seconds behind and "-0:00.40" for 0.4 seconds ahead.
+#[must_use]
+pub fn format_delta(delta: SignedDuration) -> String {
+    let secs = delta.magnitude().as_secs();
+    let millis = delta.magnitude().subsec_millis();
+    let sign = if delta.is_negative() { '-' } else { '+' };
+    format!("{sign}{}:{:02}.{:02}", secs / 60, secs % 60, millis / 10)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::time_source::MockTimeSource;
+
+    fn mock_stopwatch() -> (Stopwatch<MockTimeSource>, MockTimeSource) {
+        let clock = MockTimeSource::new();
+        (Stopwatch::with_time_source(clock.clone()), clock)
+    }
 
     #[test]
     fn test_stopwatch_start_stop() {
-        let mut stopwatch = Stopwatch::new();
+        let (mut stopwatch, clock) = mock_stopwatch();
 
         assert!(!stopwatch.is_running());
         assert_eq!(stopwatch.elapsed_time(), Duration::from_secs(0));
 
         stopwatch.start();
-        std::thread::sleep(Duration::from_secs(1));
+        clock.advance(Duration::from_secs(1));
         stopwatch.stop();
 
         assert!(!stopwatch.is_running());
@@ -248,10 +923,10 @@ mod tests {
 
     #[test]
     fn test_stopwatch_reset() {
-        let mut stopwatch = Stopwatch::new();
+        let (mut stopwatch, clock) = mock_stopwatch();
 
         stopwatch.start();
-        std::thread::sleep(Duration::from_secs(1));
+        clock.advance(Duration::from_secs(1));
         stopwatch.stop();
 
         assert!(!stopwatch.is_running());
@@ -265,14 +940,14 @@ mod tests {
 
     #[test]
     fn test_stopwatch_add_lap() {
-        let mut stopwatch = Stopwatch::new();
+        let (mut stopwatch, clock) = mock_stopwatch();
 
         stopwatch.start();
-        std::thread::sleep(Duration::from_secs(1));
+        clock.advance(Duration::from_secs(1));
         stopwatch.add_lap();
-        std::thread::sleep(Duration::from_secs(2));
+        clock.advance(Duration::from_secs(2));
         stopwatch.add_lap();
-        std::thread::sleep(Duration::from_secs(3));
+        clock.advance(Duration::from_secs(3));
         stopwatch.stop();
 
         let lap_times = stopwatch.lap_times();
@@ -295,27 +970,414 @@ mod tests {
         assert_eq!(format_time(duration), "01:05.12");
     }
 
+    #[test]
+    fn test_stopwatch_format_signed_time() {
+        let duration = Duration::new(42, 500_000_000);
+
+        assert_eq!(
+            format_signed_time(SignedDuration::negative(duration)),
+            "-00:42.50"
+        );
+        assert_eq!(
+            format_signed_time(SignedDuration::positive(duration)),
+            "00:42.50"
+        );
+    }
+
+    #[test]
+    fn test_stopwatch_format_delta() {
+        assert_eq!(
+            format_delta(SignedDuration::positive(Duration::from_millis(1230))),
+            "+0:01.23"
+        );
+        assert_eq!(
+            format_delta(SignedDuration::negative(Duration::from_millis(400))),
+            "-0:00.40"
+        );
+        assert_eq!(
+            format_delta(SignedDuration::negative(Duration::from_secs(754))),
+            "-12:34.00"
+        );
+        assert_eq!(format_delta(SignedDuration::ZERO), "+0:00.00");
+    }
+
     #[test]
     fn test_elapsed_time() {
-        let mut timer = Stopwatch::new();
+        let (mut timer, clock) = mock_stopwatch();
         timer.start();
-        std::thread::sleep(std::time::Duration::from_secs(2));
+        clock.advance(Duration::from_secs(2));
         timer.stop();
-        std::thread::sleep(std::time::Duration::from_secs(1));
+        clock.advance(Duration::from_secs(1));
         assert_eq!(timer.elapsed_time().as_secs(), 2);
 
         timer.start();
-        std::thread::sleep(std::time::Duration::from_secs(2));
+        clock.advance(Duration::from_secs(2));
         assert_eq!(timer.elapsed_time().as_secs(), 4);
 
         timer.reset();
         assert_eq!(timer.elapsed_time().as_secs(), 0);
 
         timer.start();
-        std::thread::sleep(std::time::Duration::from_secs(1));
+        clock.advance(Duration::from_secs(1));
         timer.add_lap();
-        std::thread::sleep(std::time::Duration::from_secs(1));
+        clock.advance(Duration::from_secs(1));
         timer.stop();
         assert_eq!(timer.elapsed_time().as_secs(), 2);
     }
+
+    #[test]
+    fn test_wall_clock_changes_keep_session() {
+        let (mut stopwatch, clock) = mock_stopwatch();
+        stopwatch.start();
+        clock.advance(Duration::from_secs(5));
+        stopwatch.add_lap();
+        let started_at = stopwatch.started_at().unwrap();
+
+        clock.set_wall_clock(started_at - Duration::from_hours(1));
+        clock.advance(Duration::from_secs(5));
+        stopwatch.add_lap();
+
+        assert!(stopwatch.is_running());
+        assert_eq!(stopwatch.elapsed_time(), Duration::from_secs(10));
+        assert_eq!(stopwatch.lap_times().len(), 2);
+        assert_eq!(
+            stopwatch.lap_times()[1].split_time(),
+            Duration::from_secs(5)
+        );
+        assert_eq!(stopwatch.started_at(), Some(started_at));
+
+        stopwatch.stop();
+        stopwatch.start();
+        assert_eq!(stopwatch.elapsed_time(), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_lap_split_after_pause() {
+        let (mut stopwatch, clock) = mock_stopwatch();
+        stopwatch.start();
+        clock.advance(Duration::from_secs(1));
+        stopwatch.add_lap();
+        clock.advance(Duration::from_secs(1));
+        stopwatch.stop();
+        clock.advance(Duration::from_secs(30));
+        stopwatch.start();
+        clock.advance(Duration::from_secs(1));
+        stopwatch.add_lap();
+
+        assert_eq!(
+            stopwatch.lap_times()[1].split_time(),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            stopwatch.lap_times()[1].total_time(),
+            Duration::from_secs(3)
+        );
+    }
+
+    #[test]
+    fn test_try_variants_report_state_errors() {
+        let (mut stopwatch, clock) = mock_stopwatch();
+
+        assert_eq!(stopwatch.try_stop(), Err(ClockError::NotRunning));
+        assert_eq!(stopwatch.try_add_lap().unwrap_err(), ClockError::NotRunning);
+        assert_eq!(stopwatch.try_start(), Ok(()));
+        assert_eq!(stopwatch.try_start(), Err(ClockError::AlreadyRunning));
+
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(stopwatch.try_elapsed_time(), Ok(Duration::from_secs(2)));
+        assert_eq!(stopwatch.try_stop(), Ok(()));
+        assert_eq!(stopwatch.elapsed_time(), Duration::from_secs(2));
+    }
+
+    /// A time source that can be moved backwards, which a monotonic clock never does.
+    #[derive(Debug, Clone)]
+    struct BackwardsTimeSource(std::rc::Rc<std::cell::Cell<Instant>>);
+
+    impl TimeSource for BackwardsTimeSource {
+        fn now(&self) -> Instant {
+            self.0.get()
+        }
+
+        fn wall_clock(&self) -> SystemTime {
+            SystemTime::now()
+        }
+    }
+
+    #[test]
+    fn test_clock_going_backwards_keeps_laps() {
+        let now = Instant::now();
+        let source = BackwardsTimeSource(std::rc::Rc::new(std::cell::Cell::new(now)));
+        let mut stopwatch = Stopwatch::with_time_source(source.clone());
+
+        stopwatch.start();
+        source.0.set(now + Duration::from_secs(5));
+        stopwatch.add_lap();
+        stopwatch.stop();
+        stopwatch.start();
+
+        source.0.set(now);
+        assert_eq!(
+            stopwatch.try_elapsed_time(),
+            Err(ClockError::ClockWentBackwards)
+        );
+        assert_eq!(
+            stopwatch.try_add_lap().unwrap_err(),
+            ClockError::ClockWentBackwards
+        );
+        assert_eq!(stopwatch.try_stop(), Err(ClockError::ClockWentBackwards));
+
+        assert!(stopwatch.is_running());
+        assert_eq!(stopwatch.lap_times().len(), 1);
+        assert_eq!(stopwatch.elapsed_time(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_elapsed_time_is_exact_with_mock_time_source() {
+        let (mut stopwatch, clock) = mock_stopwatch();
+        stopwatch.start();
+        clock.advance(Duration::from_millis(1500));
+        stopwatch.stop();
+        clock.advance(Duration::from_secs(10));
+        stopwatch.start();
+        clock.advance(Duration::from_millis(250));
+        assert_eq!(stopwatch.elapsed_time(), Duration::from_millis(1750));
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let (mut stopwatch, clock) = mock_stopwatch();
+        stopwatch.start();
+        clock.advance(Duration::from_secs(4));
+        stopwatch.add_lap();
+        clock.advance(Duration::from_secs(2));
+
+        let snapshot = stopwatch.snapshot();
+        clock.advance(Duration::from_secs(10));
+        let mut restored = Stopwatch::restore_with_time_source(snapshot, clock.clone());
+        assert!(restored.is_running());
+        assert_eq!(restored.started_at(), stopwatch.started_at());
+        assert_eq!(restored.elapsed_time(), Duration::from_secs(16));
+        restored.add_lap();
+        assert_eq!(
+            restored.lap_times()[1].split_time(),
+            Duration::from_secs(12)
+        );
+
+        restored.stop();
+        let snapshot = restored.snapshot();
+        clock.advance(Duration::from_secs(10));
+        let restored = Stopwatch::restore_with_time_source(snapshot, clock.clone());
+        assert!(!restored.is_running());
+        assert_eq!(restored.elapsed_time(), Duration::from_secs(16));
+    }
+
+    #[test]
+    fn test_edit_laps() {
+        let (mut stopwatch, clock) = mock_stopwatch();
+        stopwatch.start();
+        for secs in [10, 1, 9, 10] {
+            clock.advance(Duration::from_secs(secs));
+            stopwatch.add_lap();
+        }
+
+        stopwatch
+            .lap_mut(3)
+            .unwrap()
+            .set_label(Some("Uphill".to_string()));
+        stopwatch
+            .lap_mut(4)
+            .unwrap()
+            .set_note(Some("Tired".to_string()));
+        assert!(stopwatch.lap_mut(0).is_none());
+        assert!(stopwatch.lap_mut(5).is_none());
+
+        let removed = stopwatch.remove_lap(2).unwrap();
+        assert_eq!(removed.total_time(), Duration::from_secs(11));
+        assert!(stopwatch.remove_lap(4).is_none());
+        let laps = stopwatch.lap_times();
+        assert_eq!(laps.len(), 3);
+        assert_eq!(laps[1].lap_number(), 2);
+        assert_eq!(laps[1].split_time(), Duration::from_secs(10));
+        assert_eq!(laps[1].label(), Some("Uphill"));
+        assert_eq!(laps[2].lap_number(), 3);
+        assert_eq!(laps[2].note(), Some("Tired"));
+
+        let inserted = stopwatch.insert_lap(Duration::from_secs(5)).unwrap();
+        assert_eq!(inserted.lap_number(), 1);
+        assert_eq!(inserted.split_time(), Duration::from_secs(5));
+        let laps = stopwatch.lap_times();
+        assert_eq!(laps.len(), 4);
+        assert_eq!(laps[1].lap_number(), 2);
+        assert_eq!(laps[1].split_time(), Duration::from_secs(5));
+        assert_eq!(laps[2].label(), Some("Uphill"));
+
+        assert_eq!(
+            stopwatch.insert_lap(Duration::from_secs(31)),
+            Err(ClockError::LapAfterElapsedTime)
+        );
+        let inserted = stopwatch.insert_lap(Duration::from_secs(30)).unwrap();
+        assert_eq!(inserted.lap_number(), 5);
+        assert_eq!(inserted.split_time(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_delayed_start() {
+        let (mut stopwatch, clock) = mock_stopwatch();
+        stopwatch.start_after(Duration::from_secs(10));
+        assert!(stopwatch.is_counting_down());
+        assert!(!stopwatch.is_running());
+        assert_eq!(stopwatch.try_start(), Err(ClockError::AlreadyRunning));
+        assert_eq!(stopwatch.try_add_lap().unwrap_err(), ClockError::NotRunning);
+
+        clock.advance(Duration::from_secs(4));
+        assert_eq!(
+            stopwatch.countdown_remaining(),
+            Some(Duration::from_secs(6))
+        );
+        assert_eq!(stopwatch.elapsed_time(), Duration::ZERO);
+        assert_eq!(stopwatch.started_at(), None);
+
+        let snapshot = stopwatch.snapshot();
+        assert!(stopwatch.cancel_countdown());
+        assert!(!stopwatch.cancel_countdown());
+        clock.advance(Duration::from_secs(20));
+        assert!(!stopwatch.is_running());
+        assert_eq!(stopwatch.elapsed_time(), Duration::ZERO);
+
+        stopwatch.start_after(Duration::from_secs(3));
+        clock.advance(Duration::from_secs(5));
+        assert!(stopwatch.is_running());
+        assert!(!stopwatch.is_counting_down());
+        assert!(!stopwatch.cancel_countdown());
+        let lap = stopwatch.try_add_lap().unwrap();
+        assert_eq!(lap.total_time(), Duration::from_secs(2));
+        assert_eq!(
+            stopwatch.started_at(),
+            Some(clock.wall_clock() - Duration::from_secs(2))
+        );
+
+        let restored = Stopwatch::restore_with_time_source(snapshot.clone(), clock.clone());
+        assert!(restored.is_running());
+        assert_eq!(restored.elapsed_time(), Duration::from_secs(19));
+
+        let clock = MockTimeSource::starting_at(snapshot.saved_at());
+        clock.advance(Duration::from_secs(1));
+        let restored = Stopwatch::restore_with_time_source(snapshot, clock.clone());
+        assert_eq!(restored.countdown_remaining(), Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_segments() {
+        let (mut stopwatch, clock) = mock_stopwatch();
+        assert!(stopwatch.segments().is_empty());
+        assert_eq!(stopwatch.paused_time(), Duration::ZERO);
+
+        let first_start = clock.wall_clock();
+        stopwatch.start();
+        clock.advance(Duration::from_secs(3));
+        stopwatch.stop();
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(stopwatch.paused_time(), Duration::from_secs(10));
+        stopwatch.start_after(Duration::from_secs(5));
+        clock.advance(Duration::from_secs(7));
+
+        let segments = stopwatch.segments();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].started_at(), first_start);
+        assert_eq!(
+            segments[0].stopped_at(),
+            Some(first_start + Duration::from_secs(3))
+        );
+        assert_eq!(
+            segments[1].started_at(),
+            first_start + Duration::from_secs(18)
+        );
+        assert_eq!(segments[1].duration(), Duration::from_secs(2));
+        assert!(segments[1].is_open());
+        assert_eq!(stopwatch.paused_time(), Duration::from_secs(15));
+        assert_eq!(stopwatch.elapsed_time(), Duration::from_secs(5));
+
+        let snapshot = stopwatch.snapshot();
+        clock.advance(Duration::from_secs(4));
+        let mut restored = Stopwatch::restore_with_time_source(snapshot, clock.clone());
+        restored.stop();
+        let segments = restored.segments();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[1].duration(), Duration::from_secs(6));
+        assert_eq!(
+            segments.iter().map(Segment::duration).sum::<Duration>(),
+            restored.elapsed_time()
+        );
+
+        stopwatch.reset();
+        assert!(stopwatch.segments().is_empty());
+        assert_eq!(stopwatch.started_at(), None);
+    }
+
+    #[test]
+    fn test_paused_time_ignores_wall_clock_changes() {
+        let (mut stopwatch, clock) = mock_stopwatch();
+        stopwatch.start();
+        clock.advance(Duration::from_secs(3));
+        stopwatch.stop();
+        clock.advance(Duration::from_secs(10));
+        clock.set_wall_clock(clock.wall_clock() - Duration::from_hours(1));
+        assert_eq!(stopwatch.paused_time(), Duration::from_secs(10));
+        clock.set_wall_clock(clock.wall_clock() + Duration::from_hours(2));
+        stopwatch.start();
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(stopwatch.paused_time(), Duration::from_secs(10));
+
+        stopwatch.stop();
+        clock.advance(Duration::from_secs(5));
+        let snapshot = stopwatch.snapshot();
+        clock.advance(Duration::from_secs(20));
+        let restored = Stopwatch::restore_with_time_source(snapshot, clock.clone());
+        assert_eq!(restored.paused_time(), Duration::from_secs(35));
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(restored.paused_time(), Duration::from_secs(36));
+    }
+
+    #[test]
+    fn test_lap_recorded_at() {
+        use chrono::{FixedOffset, TimeZone, Utc};
+
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 16, 30, 0).unwrap();
+        let clock = MockTimeSource::starting_at(start.into());
+        let mut stopwatch = Stopwatch::with_time_source(clock.clone());
+        stopwatch.start();
+        clock.advance(Duration::from_secs(5));
+        stopwatch.add_lap();
+        stopwatch.stop();
+        clock.advance(Duration::from_mins(1));
+        stopwatch.start();
+        clock.advance(Duration::from_secs(5));
+        stopwatch.add_lap();
+
+        let laps = stopwatch.lap_times().to_vec();
+        let in_utc = |lap: &LapTime| lap.recorded_at().map(|time| time.with_timezone(&Utc));
+        assert_eq!(in_utc(&laps[0]), Some(start + chrono::Duration::seconds(5)));
+        assert_eq!(
+            in_utc(&laps[1]),
+            Some(start + chrono::Duration::seconds(70))
+        );
+
+        let inserted = stopwatch.insert_lap(Duration::from_secs(7)).unwrap();
+        assert_eq!(
+            in_utc(&inserted),
+            Some(start + chrono::Duration::seconds(67))
+        );
+        assert_eq!(
+            LapTime::new(1, Duration::ZERO, Duration::ZERO).recorded_at(),
+            None
+        );
+
+        let tz = FixedOffset::east_opt(3600).unwrap();
+        let lap = laps[0].clone().with_recorded_at(&start.with_timezone(&tz));
+        assert_eq!(
+            lap.recorded_at_iso8601().as_deref(),
+            Some("2024-03-01T17:30:00+01:00")
+        );
+        assert_eq!(lap.recorded_at().unwrap().offset(), &tz);
+    }
 }
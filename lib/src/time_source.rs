@@ -0,0 +1,122 @@
+//! A module that defines the sources of time used by timers and stopwatches
+//!
+//! [`Timer`](crate::timer::Timer) and [`Stopwatch`](crate::stopwatch::Stopwatch) never read the
+//! clock directly. Instead they ask a [`TimeSource`], which is [`SystemTimeSource`] by default and
+//! can be replaced by a [`MockTimeSource`] to drive them deterministically.
+//!
+//! A [`TimeSource`] provides two clocks: a monotonic one that is used to measure elapsed time, and
+//! a wall clock that is only used to show or persist when something happened. Changes to the wall
+//! clock (an NTP step, the user changing the time) therefore never affect measured durations.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// A source of the current time.
+pub trait TimeSource {
+    /// Returns the current monotonic time, used to measure elapsed time.
+    fn now(&self) -> Instant;
+
+    /// Returns the current wall-clock time, used only for display and persistence.
+    fn wall_clock(&self) -> SystemTime;
+}
+
+/// A [`TimeSource`] that reads the system clocks.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SystemTimeSource;
+
+impl TimeSource for SystemTimeSource {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn wall_clock(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A [`TimeSource`] whose time only moves when it is advanced manually.
+///
+/// Clones share the same underlying clocks, so a clone handed to a timer can be advanced from the
+/// outside. The wall clock can also be moved on its own with [`MockTimeSource::set_wall_clock`] to
+/// simulate the system time being changed.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use clock::time_source::{MockTimeSource, TimeSource};
+///
+/// let source = MockTimeSource::new();
+/// let start = source.now();
+///
+/// source.advance(Duration::from_secs(3));
+///
+/// assert_eq!(source.now().duration_since(start), Duration::from_secs(3));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockTimeSource {
+    clocks: Arc<Mutex<MockClocks>>,
+}
+
+/// The clocks shared by all clones of a [`MockTimeSource`].
+#[derive(Debug)]
+struct MockClocks {
+    now: Instant,
+    wall_clock: SystemTime,
+}
+
+impl MockTimeSource {
+    /// Creates a new [`MockTimeSource`] whose wall clock starts at the current system time.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::starting_at(SystemTime::now())
+    }
+
+    /// Creates a new [`MockTimeSource`] whose wall clock starts at the given time.
+    #[must_use]
+    pub fn starting_at(wall_clock: SystemTime) -> Self {
+        Self {
+            clocks: Arc::new(Mutex::new(MockClocks {
+                now: Instant::now(),
+                wall_clock,
+            })),
+        }
+    }
+
+    /// Moves both clocks of this source (and all of its clones) forward by `duration`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock has been poisoned.
+    pub fn advance(&self, duration: Duration) {
+        let mut clocks = self.clocks.lock().unwrap();
+        clocks.now += duration;
+        clocks.wall_clock += duration;
+    }
+
+    /// Sets the wall clock of this source (and all of its clones) to `wall_clock`, leaving the
+    /// monotonic clock untouched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock has been poisoned.
+    pub fn set_wall_clock(&self, wall_clock: SystemTime) {
+        self.clocks.lock().unwrap().wall_clock = wall_clock;
+    }
+}
+
+impl Default for MockTimeSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeSource for MockTimeSource {
+    fn now(&self) -> Instant {
+        self.clocks.lock().unwrap().now
+    }
+
+    fn wall_clock(&self) -> SystemTime {
+        self.clocks.lock().unwrap().wall_clock
+    }
+}
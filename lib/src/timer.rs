@@ -1,45 +1,201 @@
 //! A module that defines all the core functions and structs used to create timers
 
-use std::time::{Duration, SystemTime};
+use std::fmt;
+use std::time::{Duration, Instant, SystemTime};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::duration::SignedDuration;
+use crate::error::ClockError;
+use crate::snapshot::{self, TimerSnapshot};
+use crate::time_source::{SystemTimeSource, TimeSource};
+
+/// The state of a [`Timer`].
+///
+/// A [`Timer`] starts out [`Idle`](TimerState::Idle), alternates between
+/// [`Running`](TimerState::Running) and [`Paused`](TimerState::Paused) as it is started and
+/// stopped, becomes [`Expired`](TimerState::Expired) once no time remains, and returns to
+/// [`Idle`](TimerState::Idle) when it is reset.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TimerState {
+    /// The timer has not been started since it was created or last reset.
+    Idle,
+    /// The timer is counting down.
+    Running,
+    /// The timer was started and then stopped before it expired.
+    Paused,
+    /// The timer ran out of time.
+    Expired,
+}
+
+impl TimerState {
+    /// Returns whether a [`Timer`] may move from this state to `next`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use clock::timer::TimerState;
+    ///
+    /// assert!(TimerState::Idle.can_transition_to(TimerState::Running));
+    /// assert!(TimerState::Expired.can_transition_to(TimerState::Idle));
+    /// assert!(!TimerState::Expired.can_transition_to(TimerState::Running));
+    /// assert!(!TimerState::Idle.can_transition_to(TimerState::Paused));
+    /// ```
+    #[must_use]
+    pub fn can_transition_to(self, next: TimerState) -> bool {
+        matches!(
+            (self, next),
+            (TimerState::Idle | TimerState::Paused, TimerState::Running)
+                | (
+                    TimerState::Running,
+                    TimerState::Paused | TimerState::Expired
+                )
+                | (_, TimerState::Idle)
+        )
+    }
+}
+
+impl fmt::Display for TimerState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            TimerState::Idle => "idle",
+            TimerState::Running => "running",
+            TimerState::Paused => "paused",
+            TimerState::Expired => "expired",
+        })
+    }
+}
+
+/// What a [`Timer`] does once it runs out of time.
+///
+/// The policy is applied by the [`Timer`] itself, so every front-end behaves the same way.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CompletionPolicy {
+    /// The timer stays [`Expired`](TimerState::Expired) with no time remaining until it is reset.
+    #[default]
+    StayExpired,
+    /// The timer resets itself and goes back to [`Idle`](TimerState::Idle).
+    AutoReset,
+    /// The timer starts counting down again this many times before it stays expired, so
+    /// `Repeat(2)` runs it three times in total. Every run is scheduled from the moment the timer
+    /// was started, so no time is lost between runs.
+    Repeat(u32),
+    /// The timer starts counting down again every time it runs out, until it is stopped or reset.
+    /// Like with [`Repeat`](CompletionPolicy::Repeat), the runs never drift from the moment the
+    /// timer was started.
+    RepeatForever,
+    /// The timer stays [`Expired`](TimerState::Expired) but keeps counting past zero, see
+    /// [`Timer::signed_remaining`] and [`Timer::overtime`].
+    Overtime,
+}
+
+/// A point before a [`Timer`] expires at which it warns that time is running out, see
+/// [`Timer::add_warning`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum WarningThreshold {
+    /// Warn once this much time is left, e.g. "5 minutes left".
+    Remaining(Duration),
+    /// Warn once this percentage of the duration is left. Values above 100 are treated as 100.
+    PercentRemaining(u8),
+}
+
+/// A [`WarningThreshold`] of a [`Timer`] and whether it was already reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(crate) struct Warning {
+    /// The threshold to warn at.
+    threshold: WarningThreshold,
+    /// The run, counted by [`Timer::completions`], in which the threshold was last reported, or
+    /// `None` if it has not been reported since it was last re-armed.
+    reported_run: Option<u32>,
+}
+
+/// The state of a [`Timer`] at a given moment, after its [`CompletionPolicy`] has been applied.
+#[derive(Debug, Clone, Copy)]
+struct Resolved {
+    /// The state of the timer.
+    state: TimerState,
+    /// The elapsed time of the current run.
+    elapsed: Duration,
+    /// The number of runs that completed since the timer was last reset.
+    completed_runs: u32,
+}
 
 /// A timer that can be started, stopped, reset, and queried for its elapsed and remaining time.
 ///
+/// The timer reads the current time from a [`TimeSource`], which is the system clock unless
+/// another source is given with [`Timer::with_time_source`]. Elapsed and remaining time are
+/// measured on the monotonic clock, so changes to the wall clock never make them jump.
+///
+/// # Waiting
+///
+/// A `Timer` only computes its state when it is queried, so it cannot block until it expires.
+/// To wait for a timer, hand it to a [`TimerDriver`](crate::driver::TimerDriver) and call
+/// [`TimerDriver::wait`](crate::driver::TimerDriver::wait) or
+/// [`TimerDriver::wait_timeout`](crate::driver::TimerDriver::wait_timeout).
+///
 /// # Examples
 ///
 /// ```
 /// use std::time::Duration;
+/// use clock::time_source::MockTimeSource;
 /// use clock::timer::Timer;
 ///
+/// let clock = MockTimeSource::new();
+///
 /// // Create a timer with a duration of 5 seconds
-/// let mut timer = Timer::new(Duration::from_secs(5));
+/// let mut timer = Timer::with_time_source(Duration::from_secs(5), clock.clone());
 ///
 /// // Start the timer
 /// timer.start();
 ///
-/// // Wait for some time (e.g., 3 seconds)
-/// std::thread::sleep(Duration::from_secs(3));
+/// // Let some time pass (e.g., 3 seconds)
+/// clock.advance(Duration::from_secs(3));
 ///
 /// // Stop the timer
 /// timer.stop();
 ///
 ///  timer.start();
 ///
-/// // Wait for the remaining time (e.g., 2 seconds)
-/// std::thread::sleep(timer.remaining());
+/// // Let the remaining time pass (e.g., 2 seconds)
+/// clock.advance(timer.remaining());
 ///
 /// // Check that the timer is now expired
 /// assert_eq!(timer.remaining(), Duration::from_secs(0));
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct Timer {
-    /// An [`SystemTime`] representing the time when the timer was started or last resumed.
-    start_time: SystemTime,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timer<T: TimeSource = SystemTimeSource> {
+    /// An [`Instant`] representing the time when the timer was started or last resumed.
+    start_time: Instant,
+    /// A [`SystemTime`] representing the wall-clock time when the timer was started or last resumed,
+    /// or `None` if it has never been started. Only used for display and persistence.
+    wall_start_time: Option<SystemTime>,
     /// A [`Duration`] representing the total time that the timer has been running since it was last started or resumed.
     elapsed_time: Duration,
     /// A [`Duration`] representing the total duration of the timer.
     duration: Duration,
-    /// A boolean indicating whether the timer is currently running or stopped.
-    is_running: bool,
+    /// The state the timer was last moved to. This is never [`TimerState::Expired`], which is
+    /// derived from the remaining time by [`Timer::state`].
+    state: TimerState,
+    /// What the timer does once it runs out of time.
+    completion_policy: CompletionPolicy,
+    /// The number of runs that completed since the timer was last reset and have been folded into
+    /// the stored state.
+    completed_runs: u32,
+    /// Whether the expiry the timer is currently in is already counted in `completed_runs`.
+    expiry_counted: bool,
+    /// The number of runs that completed before the timer was last reset.
+    past_completions: u32,
+    /// The thresholds the timer warns at before it expires.
+    warnings: Vec<Warning>,
+    /// The [`TimeSource`] the timer reads the current time from.
+    time_source: T,
 }
 
 impl Timer {
@@ -59,17 +215,75 @@ impl Timer {
     /// ```
     #[must_use]
     pub fn new(duration: Duration) -> Timer {
+        Timer::with_time_source(duration, SystemTimeSource)
+    }
+
+    /// Creates a [`Timer`] from a snapshot taken with [`Timer::snapshot`] that reads the system
+    /// clock, see [`Timer::restore_with_time_source`].
+    #[must_use]
+    pub fn restore(snapshot: TimerSnapshot) -> Timer {
+        Timer::restore_with_time_source(snapshot, SystemTimeSource)
+    }
+}
+
+impl<T: TimeSource> Timer<T> {
+    /// Creates a new [`Timer`] that reads the current time from `time_source`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use clock::time_source::MockTimeSource;
+    /// use clock::timer::Timer;
+    ///
+    /// let clock = MockTimeSource::new();
+    /// let mut timer = Timer::with_time_source(Duration::from_secs(5), clock.clone());
+    ///
+    /// timer.start();
+    /// clock.advance(Duration::from_secs(2));
+    ///
+    /// assert_eq!(timer.remaining(), Duration::from_secs(3));
+    /// ```
+    #[must_use]
+    pub fn with_time_source(duration: Duration, time_source: T) -> Timer<T> {
         Timer {
-            start_time: SystemTime::now(),
+            start_time: time_source.now(),
+            wall_start_time: None,
             elapsed_time: Duration::default(),
             duration,
-            is_running: false,
+            state: TimerState::Idle,
+            completion_policy: CompletionPolicy::StayExpired,
+            completed_runs: 0,
+            expiry_counted: false,
+            past_completions: 0,
+            warnings: Vec::new(),
+            time_source,
         }
     }
 
+    /// Returns the [`TimeSource`] the timer reads the current time from.
+    #[must_use]
+    pub fn time_source(&self) -> &T {
+        &self.time_source
+    }
+
+    /// Returns how long the timer has been running since it was last started or resumed.
+    fn running_time(&self) -> Duration {
+        self.time_source.now().duration_since(self.start_time)
+    }
+
+    /// Returns how long the timer has been running since it was last started or resumed, failing
+    /// if the time source reports a time before that.
+    fn try_running_time(&self) -> Result<Duration, ClockError> {
+        self.time_source
+            .now()
+            .checked_duration_since(self.start_time)
+            .ok_or(ClockError::ClockWentBackwards)
+    }
+
     /// Starts the [`Timer`].
     ///
-    /// If the [`Timer`] is already running, this method has no effect.
+    /// If the [`Timer`] is already running or has expired, this method has no effect.
     ///
     /// # Examples
     ///
@@ -83,39 +297,79 @@ impl Timer {
     /// timer.start();
     /// ```
     pub fn start(&mut self) {
-        if !self.is_running {
-            self.is_running = true;
-            self.start_time = SystemTime::now();
+        let _ = self.try_start();
+    }
+
+    /// Starts the [`Timer`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClockError::AlreadyRunning`] if the [`Timer`] is already running, or
+    /// [`ClockError::InvalidTransition`] if it has expired and must be reset first.
+    pub fn try_start(&mut self) -> Result<(), ClockError> {
+        self.settle();
+        self.check_transition(TimerState::Running)?;
+
+        self.state = TimerState::Running;
+        self.start_time = self.time_source.now();
+        self.wall_start_time = Some(self.time_source.wall_clock());
+        Ok(())
+    }
+
+    /// Starts a [`Timer`] that has not been started yet as if it had been started `late` ago, so
+    /// that a timer following another one picks up exactly where the previous one ran out.
+    pub(crate) fn start_late(&mut self, late: Duration) {
+        if self.state == TimerState::Idle && self.try_start().is_ok() {
+            self.elapsed_time = late;
         }
     }
 
     /// Stops the [`Timer`].
     ///
-    /// If the [`Timer`] is already stopped, this method has no effect.
+    /// If the [`Timer`] is already stopped or has expired, this method has no effect.
     ///
     /// # Examples
     ///
     /// ```
+    /// use clock::time_source::MockTimeSource;
     /// use clock::timer::Timer;
     /// use std::time::Duration;
     ///
+    /// let clock = MockTimeSource::new();
     /// let duration = Duration::from_secs(5);
-    /// let mut timer = Timer::new(duration);
+    /// let mut timer = Timer::with_time_source(duration, clock.clone());
     ///
     /// timer.start();
-    /// std::thread::sleep(std::time::Duration::from_secs(1));
+    /// clock.advance(Duration::from_secs(1));
     /// timer.stop();
     /// let elapsed = timer.elapsed();
     /// assert!(elapsed > Duration::from_secs(0));
     /// assert!(elapsed < Duration::from_secs(5));
     /// ```
     pub fn stop(&mut self) {
-        if self.is_running {
-            self.elapsed_time += self.start_time.elapsed().unwrap_or_default();
-            self.is_running = false;
+        self.settle();
+        if self.state() == TimerState::Running {
+            self.elapsed_time += self.running_time();
+            self.state = TimerState::Paused;
         }
     }
 
+    /// Stops the [`Timer`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClockError::NotRunning`] if the [`Timer`] is already stopped,
+    /// [`ClockError::InvalidTransition`] if it has expired, or [`ClockError::ClockWentBackwards`]
+    /// if the time source reports a time before the [`Timer`] was last started.
+    pub fn try_stop(&mut self) -> Result<(), ClockError> {
+        self.settle();
+        self.check_transition(TimerState::Paused)?;
+
+        self.elapsed_time += self.try_running_time()?;
+        self.state = TimerState::Paused;
+        Ok(())
+    }
+
     /// Resets the [`Timer`] to its initial state.
     ///
     /// The [`Timer`] will have zero elapsed time and will be in the stopped state.
@@ -123,23 +377,41 @@ impl Timer {
     /// # Examples
     ///
     /// ```
+    /// use clock::time_source::MockTimeSource;
     /// use clock::timer::Timer;
     /// use std::time::Duration;
     ///
+    /// let clock = MockTimeSource::new();
     /// let duration = Duration::from_secs(5);
-    /// let mut timer = Timer::new(duration);
+    /// let mut timer = Timer::with_time_source(duration, clock.clone());
     ///
     /// timer.start();
-    /// std::thread::sleep(std::time::Duration::from_secs(1));
+    /// clock.advance(Duration::from_secs(1));
     /// timer.reset();
     ///
     /// assert_eq!(timer.elapsed(), Duration::from_secs(0));
     /// assert_eq!(timer.remaining(), Duration::from_secs(5));
     /// ```
     pub fn reset(&mut self) {
-        self.start_time = SystemTime::now();
+        self.past_completions += self.resolve(self.raw_elapsed()).completed_runs;
+        self.completed_runs = 0;
+        self.expiry_counted = false;
+        for warning in &mut self.warnings {
+            warning.reported_run = None;
+        }
+        self.start_time = self.time_source.now();
+        self.wall_start_time = None;
         self.elapsed_time = Duration::default();
-        self.is_running = false;
+        self.state = TimerState::Idle;
+    }
+
+    /// Returns the time the timer has run, including any time past its duration.
+    fn raw_elapsed(&self) -> Duration {
+        let mut elapsed = self.elapsed_time;
+        if self.state == TimerState::Running {
+            elapsed += self.running_time();
+        }
+        elapsed
     }
 
     /// Returns the elapsed time since the timer was started.
@@ -148,30 +420,36 @@ impl Timer {
     /// plus any time that has elapsed since the timer was last stopped. If the timer is currently stopped,
     /// the elapsed time is the total time that elapsed since the timer was last started and then stopped.
     ///
+    /// Once the timer has expired, the elapsed time stays at [`Timer::duration`] unless its
+    /// [`CompletionPolicy`] is [`Overtime`](CompletionPolicy::Overtime). With
+    /// [`Repeat`](CompletionPolicy::Repeat) and [`RepeatForever`](CompletionPolicy::RepeatForever),
+    /// it is the elapsed time of the current run.
+    ///
     /// # Examples
     ///
     /// ```
-    /// use std::time::{Duration, Instant};
+    /// use std::time::Duration;
+    /// use clock::time_source::MockTimeSource;
     /// use clock::timer::Timer;
     ///
+    /// let clock = MockTimeSource::new();
     /// let duration = Duration::from_secs(5);
-    /// let mut timer = Timer::new(duration);
+    /// let mut timer = Timer::with_time_source(duration, clock.clone());
     ///
     /// timer.start();
-    /// std::thread::sleep(Duration::from_secs(2));
+    /// clock.advance(Duration::from_millis(2500));
     ///
-    /// // Check the elapsed time, which should be around 3 seconds
-    /// dbg!(timer.elapsed() > Duration::from_secs(2) && timer.elapsed() < Duration::from_secs(4));
-    /// assert!(timer.elapsed() > Duration::from_secs(2) && timer.elapsed() < Duration::from_secs(4));
+    /// // Check the elapsed time, which is 2.5 seconds
+    /// assert_eq!(timer.elapsed(), Duration::from_millis(2500));
     ///
-    /// // Check the remaining time, which should be around 2 seconds
-    /// assert!(timer.remaining() > Duration::from_secs(1) && timer.remaining() < Duration::from_secs(3));
+    /// // Check the remaining time, which is also 2.5 seconds
+    /// assert_eq!(timer.remaining(), Duration::from_millis(2500));
     ///
     /// // Stop the timer
     /// timer.stop();
     ///
-    /// // Check the elapsed time again, which should be around 3 seconds
-    /// assert!(timer.elapsed() > Duration::from_secs(2) && timer.elapsed() < Duration::from_secs(4));
+    /// // Check the elapsed time again, which is still 2.5 seconds
+    /// assert_eq!(timer.elapsed(), Duration::from_millis(2500));
     ///
     /// // Reset the timer to its initial state
     /// timer.reset();
@@ -183,12 +461,116 @@ impl Timer {
     /// assert_eq!(timer.remaining(), Duration::from_secs(5));
     /// ```
     #[must_use]
-    pub fn elapsed(&mut self) -> Duration {
+    pub fn elapsed(&self) -> Duration {
+        self.resolve(self.raw_elapsed()).elapsed
+    }
+
+    /// Returns the elapsed time since the timer was started.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClockError::ClockWentBackwards`] if the timer is running and the time source
+    /// reports a time before the timer was last started.
+    pub fn try_elapsed(&self) -> Result<Duration, ClockError> {
         let mut elapsed = self.elapsed_time;
-        if self.is_running {
-            elapsed += self.start_time.elapsed().unwrap_or_default();
+        if self.state == TimerState::Running {
+            elapsed += self.try_running_time()?;
+        }
+        Ok(self.resolve(elapsed).elapsed)
+    }
+
+    /// Applies the [`CompletionPolicy`] to a timer that has run for `raw_elapsed`.
+    fn resolve(&self, raw_elapsed: Duration) -> Resolved {
+        let unexpired = Resolved {
+            state: self.state,
+            elapsed: raw_elapsed,
+            completed_runs: self.completed_runs,
+        };
+        if self.state == TimerState::Idle || raw_elapsed < self.duration {
+            return unexpired;
+        }
+
+        let expired = Resolved {
+            state: TimerState::Expired,
+            elapsed: self.duration,
+            completed_runs: self.completed_runs + u32::from(!self.expiry_counted),
+        };
+        match self.completion_policy {
+            CompletionPolicy::StayExpired => expired,
+            CompletionPolicy::Overtime => Resolved {
+                elapsed: raw_elapsed,
+                ..expired
+            },
+            CompletionPolicy::AutoReset => Resolved {
+                state: TimerState::Idle,
+                elapsed: Duration::ZERO,
+                ..expired
+            },
+            CompletionPolicy::RepeatForever if self.duration.is_zero() => expired,
+            CompletionPolicy::Repeat(_) | CompletionPolicy::RepeatForever => {
+                let repeats = match self.completion_policy {
+                    CompletionPolicy::Repeat(repeats) => repeats,
+                    _ => u32::MAX,
+                };
+                let runs = if self.duration.is_zero() {
+                    u32::MAX
+                } else {
+                    u32::try_from(raw_elapsed.as_nanos() / self.duration.as_nanos())
+                        .unwrap_or(u32::MAX)
+                };
+                let completed_runs = self.completed_runs.saturating_add(runs);
+                if self.expiry_counted {
+                    expired
+                } else if completed_runs > repeats {
+                    Resolved {
+                        completed_runs: self.completed_runs.max(repeats).saturating_add(1),
+                        ..expired
+                    }
+                } else {
+                    Resolved {
+                        elapsed: raw_elapsed.saturating_sub(self.duration * runs),
+                        completed_runs,
+                        ..unexpired
+                    }
+                }
+            }
+        }
+    }
+
+    /// Folds the [`CompletionPolicy`] into the stored state, so that changes made to an expired
+    /// timer start from the state it reports rather than from the time it kept running.
+    fn settle(&mut self) {
+        let raw_elapsed = self.raw_elapsed();
+        if self.state == TimerState::Idle || raw_elapsed < self.duration {
+            return;
+        }
+
+        let resolved = self.resolve(raw_elapsed);
+        if self.completion_policy == CompletionPolicy::AutoReset {
+            self.reset();
+            return;
+        }
+
+        self.completed_runs = resolved.completed_runs;
+        self.expiry_counted = resolved.state == TimerState::Expired;
+        if self.completion_policy != CompletionPolicy::Overtime {
+            self.elapsed_time = resolved.elapsed;
+            self.start_time = self.time_source.now();
+        }
+    }
+
+    /// Re-arms the expiry and the warning thresholds that a change gave the timer time before
+    /// again, so that they are reported again when the timer reaches them.
+    fn unsettle(&mut self) {
+        if self.raw_elapsed() < self.duration {
+            self.expiry_counted = false;
+        }
+        let remaining = self.remaining();
+        for index in 0..self.warnings.len() {
+            if self.threshold(self.warnings[index].threshold) < remaining {
+                self.warnings[index].reported_run = None;
+            }
         }
-        elapsed
     }
 
     /// Returns the remaining time until the timer expires.
@@ -197,17 +579,21 @@ impl Timer {
     /// the elapsed time from the total duration. If the timer is stopped or has not yet been started,
     /// the remaining time is simply the total duration of the timer.
     ///
+    /// For a repeating timer, this is the time left in the current cycle.
+    ///
     /// # Examples
     ///
     /// ```
     /// use std::time::Duration;
+    /// use clock::time_source::MockTimeSource;
     /// use clock::timer::Timer;
     ///
-    /// let mut timer = Timer::new(Duration::from_secs(10));
+    /// let clock = MockTimeSource::new();
+    /// let mut timer = Timer::with_time_source(Duration::from_secs(10), clock.clone());
     ///
-    /// // Start the timer and wait for a moment.
+    /// // Start the timer and let a moment pass.
     /// timer.start();
-    /// std::thread::sleep(Duration::from_secs(2));
+    /// clock.advance(Duration::from_millis(2500));
     ///
     /// // Verify that there is still some time remaining on the timer.
     /// let remaining = timer.remaining();
@@ -216,19 +602,266 @@ impl Timer {
     /// ```
     #[must_use]
     pub fn remaining(&self) -> Duration {
-        let mut remaining = self.duration();
-        if self.is_running {
-            remaining = remaining
-                .checked_sub(self.start_time.elapsed().unwrap_or_default() + self.elapsed_time)
-                .unwrap_or_default();
-        } else if self.elapsed_time != Duration::from_secs(0) {
-            remaining = remaining.checked_sub(self.elapsed_time).unwrap_or_default();
+        self.duration().saturating_sub(self.elapsed())
+    }
+
+    /// Returns the remaining time until the timer expires.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClockError::ClockWentBackwards`] if the timer is running and the time source
+    /// reports a time before the timer was last started.
+    pub fn try_remaining(&self) -> Result<Duration, ClockError> {
+        Ok(self.duration().saturating_sub(self.try_elapsed()?))
+    }
+
+    /// Returns the remaining time until the timer expires, which is negative once an expired timer
+    /// in [`Overtime`](CompletionPolicy::Overtime) has run past its duration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use clock::duration::SignedDuration;
+    /// use clock::stopwatch::format_signed_time;
+    /// use clock::time_source::MockTimeSource;
+    /// use clock::timer::Timer;
+    ///
+    /// let clock = MockTimeSource::new();
+    /// let mut timer = Timer::with_time_source(Duration::from_secs(60), clock.clone());
+    /// timer.set_overtime(true);
+    ///
+    /// timer.start();
+    /// clock.advance(Duration::from_secs(102));
+    ///
+    /// assert_eq!(timer.remaining(), Duration::ZERO);
+    /// assert_eq!(timer.signed_remaining(), SignedDuration::negative(Duration::from_secs(42)));
+    /// assert_eq!(format_signed_time(timer.signed_remaining()), "-00:42.00");
+    /// ```
+    #[must_use]
+    pub fn signed_remaining(&self) -> SignedDuration {
+        SignedDuration::difference(self.duration, self.elapsed())
+    }
+
+    /// Returns how long an expired timer in [`Overtime`](CompletionPolicy::Overtime) has run past
+    /// its duration. This is zero for any other timer.
+    #[must_use]
+    pub fn overtime(&self) -> Duration {
+        self.elapsed().saturating_sub(self.duration)
+    }
+
+    /// Enables or disables overtime. With overtime enabled, an expired timer keeps counting past
+    /// zero, which is reported by [`Timer::signed_remaining`] and [`Timer::overtime`].
+    ///
+    /// This is a shorthand for setting the [`CompletionPolicy`] to
+    /// [`Overtime`](CompletionPolicy::Overtime), or back to
+    /// [`StayExpired`](CompletionPolicy::StayExpired) if overtime was enabled.
+    pub fn set_overtime(&mut self, enabled: bool) {
+        if enabled {
+            self.set_completion_policy(CompletionPolicy::Overtime);
+        } else if self.is_overtime_enabled() {
+            self.set_completion_policy(CompletionPolicy::StayExpired);
+        }
+    }
+
+    /// Returns if overtime is enabled.
+    #[must_use]
+    pub fn is_overtime_enabled(&self) -> bool {
+        self.completion_policy == CompletionPolicy::Overtime
+    }
+
+    /// Returns what the timer does once it runs out of time.
+    #[must_use]
+    pub fn completion_policy(&self) -> CompletionPolicy {
+        self.completion_policy
+    }
+
+    /// Sets what the timer does once it runs out of time. The default is
+    /// [`StayExpired`](CompletionPolicy::StayExpired).
+    ///
+    /// If the timer has already expired, the previous policy is applied first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use clock::time_source::MockTimeSource;
+    /// use clock::timer::{CompletionPolicy, Timer, TimerState};
+    ///
+    /// let clock = MockTimeSource::new();
+    /// let mut timer = Timer::with_time_source(Duration::from_secs(60), clock.clone());
+    /// timer.set_completion_policy(CompletionPolicy::Repeat(1));
+    ///
+    /// timer.start();
+    /// clock.advance(Duration::from_secs(90));
+    /// assert_eq!(timer.state(), TimerState::Running);
+    /// assert_eq!(timer.remaining(), Duration::from_secs(30));
+    /// assert_eq!(timer.completions(), 1);
+    ///
+    /// clock.advance(Duration::from_secs(30));
+    /// assert_eq!(timer.state(), TimerState::Expired);
+    /// assert_eq!(timer.completions(), 2);
+    /// ```
+    pub fn set_completion_policy(&mut self, completion_policy: CompletionPolicy) {
+        self.settle();
+        self.completion_policy = completion_policy;
+    }
+
+    /// Returns how many times the timer has run out of time since it was created, including runs
+    /// that were repeated or reset by its [`CompletionPolicy`].
+    ///
+    /// Front-ends can watch this number to notify the user without polling for expiry themselves.
+    #[must_use]
+    pub fn completions(&self) -> u32 {
+        self.past_completions + self.resolve(self.raw_elapsed()).completed_runs
+    }
+
+    /// Returns the zero-based index of the current run since the timer was last reset, which moves
+    /// on to the next cycle every time a repeating timer starts counting down again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use clock::time_source::MockTimeSource;
+    /// use clock::timer::{CompletionPolicy, Timer};
+    ///
+    /// let clock = MockTimeSource::new();
+    /// let mut timer = Timer::with_time_source(Duration::from_secs(20 * 60), clock.clone());
+    /// timer.set_completion_policy(CompletionPolicy::RepeatForever);
+    ///
+    /// timer.start();
+    /// clock.advance(Duration::from_secs(5 * 60 * 60 + 3 * 60));
+    ///
+    /// assert_eq!(timer.cycle(), 15);
+    /// assert_eq!(timer.remaining(), Duration::from_secs(17 * 60));
+    /// assert_eq!(timer.remaining_cycles(), None);
+    /// ```
+    #[must_use]
+    pub fn cycle(&self) -> u32 {
+        let resolved = self.resolve(self.raw_elapsed());
+        match resolved.state {
+            TimerState::Idle => 0,
+            TimerState::Expired => resolved.completed_runs.saturating_sub(1),
+            TimerState::Running | TimerState::Paused => resolved.completed_runs,
+        }
+    }
+
+    /// Returns how many more cycles the timer runs after the current one, or `None` if its
+    /// [`CompletionPolicy`] is [`RepeatForever`](CompletionPolicy::RepeatForever).
+    #[must_use]
+    pub fn remaining_cycles(&self) -> Option<u32> {
+        match self.completion_policy {
+            CompletionPolicy::RepeatForever => None,
+            CompletionPolicy::Repeat(repeats) => Some(repeats.saturating_sub(self.cycle())),
+            _ => Some(0),
+        }
+    }
+
+    /// Adds a threshold at which the timer warns that time is running out.
+    ///
+    /// Every threshold is reported once per run by [`Timer::take_warnings`], as soon as the
+    /// remaining time reaches it. A change that gives the timer more time than the threshold
+    /// again, such as [`Timer::add_time`], makes it report the threshold again later. Thresholds
+    /// that are not shorter than the duration of the timer are never reported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use clock::time_source::MockTimeSource;
+    /// use clock::timer::{Timer, WarningThreshold};
+    ///
+    /// let clock = MockTimeSource::new();
+    /// let mut timer = Timer::with_time_source(Duration::from_secs(10 * 60), clock.clone());
+    /// timer.add_warning(WarningThreshold::Remaining(Duration::from_secs(5 * 60)));
+    /// timer.add_warning(WarningThreshold::Remaining(Duration::from_secs(30)));
+    ///
+    /// timer.start();
+    /// clock.advance(Duration::from_secs(6 * 60));
+    ///
+    /// assert_eq!(timer.take_warnings(), vec![0]);
+    /// assert!(timer.take_warnings().is_empty());
+    /// ```
+    pub fn add_warning(&mut self, threshold: WarningThreshold) {
+        self.warnings.push(Warning {
+            threshold,
+            reported_run: None,
+        });
+    }
+
+    /// Returns the thresholds the timer warns at, in the order they were added.
+    #[must_use]
+    pub fn warnings(&self) -> Vec<WarningThreshold> {
+        self.warnings
+            .iter()
+            .map(|warning| warning.threshold)
+            .collect()
+    }
+
+    /// Removes all warning thresholds.
+    pub fn clear_warnings(&mut self) {
+        self.warnings.clear();
+    }
+
+    /// Returns the indices, in [`Timer::warnings`], of the thresholds the timer reached since they
+    /// were last reported, and marks them as reported.
+    pub fn take_warnings(&mut self) -> Vec<usize> {
+        let run = self.completions();
+        let pending = self.pending_warnings(run);
+        for &index in &pending {
+            self.warnings[index].reported_run = Some(run);
+        }
+        pending
+    }
+
+    /// Returns how long the timer runs until it reaches the next threshold that was not reported
+    /// yet, or `None` if it is not running or has no such threshold left in this run.
+    #[must_use]
+    pub fn next_warning(&self) -> Option<Duration> {
+        if self.state() != TimerState::Running {
+            return None;
+        }
+        let run = self.completions();
+        let remaining = self.remaining();
+        self.warnings
+            .iter()
+            .filter(|warning| warning.reported_run != Some(run))
+            .map(|warning| self.threshold(warning.threshold))
+            .filter(|&threshold| threshold < self.duration)
+            .map(|threshold| remaining.saturating_sub(threshold))
+            .min()
+    }
+
+    /// Returns the indices of the thresholds that were reached in `run` and not reported yet.
+    fn pending_warnings(&self, run: u32) -> Vec<usize> {
+        if !matches!(self.state(), TimerState::Running | TimerState::Paused) {
+            return Vec::new();
+        }
+        let remaining = self.remaining();
+        (0..self.warnings.len())
+            .filter(|&index| {
+                let warning = self.warnings[index];
+                let threshold = self.threshold(warning.threshold);
+                warning.reported_run != Some(run)
+                    && threshold < self.duration
+                    && remaining <= threshold
+            })
+            .collect()
+    }
+
+    /// Returns the remaining time at which `threshold` is reached.
+    fn threshold(&self, threshold: WarningThreshold) -> Duration {
+        match threshold {
+            WarningThreshold::Remaining(remaining) => remaining,
+            WarningThreshold::PercentRemaining(percent) => {
+                self.duration * u32::from(percent.min(100)) / 100
+            }
         }
-        remaining.max(Duration::from_secs(0))
     }
 
     /// Returns the duration that was specified when the timer was created
-    /// or last reset. This duration determines how long the timer will run
+    /// or last adjusted. This duration determines how long the timer will run
     /// before it expires. This function is thread-safe and can be called from
     /// multiple threads simultaneously.ted.
     ///
@@ -248,37 +881,278 @@ impl Timer {
         self.duration
     }
 
+    /// Replaces the duration of the timer, keeping the time it has already run.
+    ///
+    /// If the new duration is not longer than [`Timer::elapsed`], the timer expires. The new
+    /// duration is kept when the timer is reset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use clock::time_source::MockTimeSource;
+    /// use clock::timer::Timer;
+    ///
+    /// let clock = MockTimeSource::new();
+    /// let mut timer = Timer::with_time_source(Duration::from_secs(60), clock.clone());
+    ///
+    /// timer.start();
+    /// clock.advance(Duration::from_secs(20));
+    /// timer.set_duration(Duration::from_secs(90));
+    ///
+    /// assert_eq!(timer.elapsed(), Duration::from_secs(20));
+    /// assert_eq!(timer.remaining(), Duration::from_secs(70));
+    /// ```
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.settle();
+        self.duration = duration;
+        self.unsettle();
+    }
+
+    /// Changes the duration of the timer so that exactly `remaining` is left, keeping the time it
+    /// has already run.
+    ///
+    /// This also works on an expired timer, which starts counting down again if it is running.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use clock::time_source::MockTimeSource;
+    /// use clock::timer::{Timer, TimerState};
+    ///
+    /// let clock = MockTimeSource::new();
+    /// let mut timer = Timer::with_time_source(Duration::from_secs(60), clock.clone());
+    ///
+    /// timer.start();
+    /// clock.advance(Duration::from_secs(75));
+    /// assert_eq!(timer.state(), TimerState::Expired);
+    ///
+    /// timer.set_remaining(Duration::from_secs(30));
+    ///
+    /// assert_eq!(timer.state(), TimerState::Running);
+    /// assert_eq!(timer.remaining(), Duration::from_secs(30));
+    /// assert_eq!(timer.duration(), Duration::from_secs(90));
+    /// ```
+    pub fn set_remaining(&mut self, remaining: Duration) {
+        self.settle();
+        self.duration = self.elapsed().saturating_add(remaining);
+        self.unsettle();
+    }
+
+    /// Adds `amount` to the remaining time of the timer, e.g. for a "+1 minute" button.
+    ///
+    /// On an expired timer, `amount` is counted from now rather than from the moment it expired.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use clock::time_source::MockTimeSource;
+    /// use clock::timer::Timer;
+    ///
+    /// let clock = MockTimeSource::new();
+    /// let mut timer = Timer::with_time_source(Duration::from_secs(60), clock.clone());
+    ///
+    /// timer.start();
+    /// clock.advance(Duration::from_secs(50));
+    /// timer.add_time(Duration::from_secs(60));
+    ///
+    /// assert_eq!(timer.remaining(), Duration::from_secs(70));
+    /// assert_eq!(timer.duration(), Duration::from_secs(120));
+    /// ```
+    pub fn add_time(&mut self, amount: Duration) {
+        self.set_remaining(self.remaining().saturating_add(amount));
+    }
+
+    /// Removes `amount` from the remaining time of the timer. If less than `amount` is left, the
+    /// timer expires.
+    pub fn subtract_time(&mut self, amount: Duration) {
+        self.set_remaining(self.remaining().saturating_sub(amount));
+    }
+
+    /// Returns the wall-clock time when the timer was last started or resumed, or `None` if it
+    /// has not been started since it was created or reset.
+    ///
+    /// This is meant for display and persistence only; elapsed and remaining time never depend on it.
+    #[must_use]
+    pub fn started_at(&self) -> Option<SystemTime> {
+        self.wall_start_time
+    }
+
+    /// Returns the wall-clock time at which the timer is expected to expire, or `None` if it is
+    /// not running.
+    ///
+    /// The estimate is based on the current wall clock, so it follows changes to the system time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use clock::time_source::{MockTimeSource, TimeSource};
+    /// use clock::timer::Timer;
+    ///
+    /// let clock = MockTimeSource::new();
+    /// let mut timer = Timer::with_time_source(Duration::from_secs(60), clock.clone());
+    /// assert_eq!(timer.expires_at(), None);
+    ///
+    /// timer.start();
+    /// clock.advance(Duration::from_secs(20));
+    ///
+    /// assert_eq!(timer.expires_at(), Some(clock.wall_clock() + Duration::from_secs(40)));
+    /// ```
+    #[must_use]
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        if self.state == TimerState::Running {
+            Some(self.time_source.wall_clock() + self.remaining())
+        } else {
+            None
+        }
+    }
+
+    /// Returns the current [`TimerState`] of the timer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use clock::time_source::MockTimeSource;
+    /// use clock::timer::{Timer, TimerState};
+    ///
+    /// let clock = MockTimeSource::new();
+    /// let mut timer = Timer::with_time_source(Duration::from_secs(5), clock.clone());
+    /// assert_eq!(timer.state(), TimerState::Idle);
+    ///
+    /// timer.start();
+    /// timer.stop();
+    /// assert_eq!(timer.state(), TimerState::Paused);
+    ///
+    /// timer.start();
+    /// clock.advance(Duration::from_secs(5));
+    /// assert_eq!(timer.state(), TimerState::Expired);
+    /// ```
+    #[must_use]
+    pub fn state(&self) -> TimerState {
+        self.resolve(self.raw_elapsed()).state
+    }
+
+    /// Checks that the timer may move from its current state to `to`.
+    fn check_transition(&self, to: TimerState) -> Result<(), ClockError> {
+        let from = self.state();
+        if from.can_transition_to(to) {
+            return Ok(());
+        }
+
+        Err(match (from, to) {
+            (TimerState::Running, TimerState::Running) => ClockError::AlreadyRunning,
+            (TimerState::Idle | TimerState::Paused, TimerState::Paused) => ClockError::NotRunning,
+            (from, to) => ClockError::InvalidTransition { from, to },
+        })
+    }
+
     /// Returns if the timer is running
     #[must_use]
     pub fn is_running(&self) -> bool {
-        self.is_running
+        self.state() == TimerState::Running
     }
 
     /// Returns if the timer is done
     #[must_use]
     pub fn is_done(&self) -> bool {
-        self.remaining().is_zero()
+        self.state() == TimerState::Expired
     }
 
     /// Returns if the timer has not started
     #[must_use]
     pub fn has_not_started(&self) -> bool {
-        self.remaining() == self.duration()
+        self.state() == TimerState::Idle
+    }
+
+    /// Returns a snapshot of the timer that can be persisted and turned back into a timer with
+    /// [`Timer::restore_with_time_source`], e.g. after the process restarts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use clock::time_source::MockTimeSource;
+    /// use clock::timer::Timer;
+    ///
+    /// let clock = MockTimeSource::new();
+    /// let mut timer = Timer::with_time_source(Duration::from_secs(60), clock.clone());
+    /// timer.start();
+    /// clock.advance(Duration::from_secs(10));
+    /// let snapshot = timer.snapshot();
+    ///
+    /// // The process is not running for 20 seconds.
+    /// clock.advance(Duration::from_secs(20));
+    ///
+    /// let timer = Timer::restore_with_time_source(snapshot, clock.clone());
+    /// assert_eq!(timer.remaining(), Duration::from_secs(30));
+    /// ```
+    #[must_use]
+    pub fn snapshot(&self) -> TimerSnapshot {
+        TimerSnapshot {
+            duration: self.duration,
+            elapsed: self.raw_elapsed(),
+            state: self.state,
+            completion_policy: self.completion_policy,
+            completed_runs: self.completed_runs,
+            expiry_counted: self.expiry_counted,
+            past_completions: self.past_completions,
+            warnings: self.warnings.clone(),
+            started_at: self.wall_start_time,
+            saved_at: self.time_source.wall_clock(),
+        }
+    }
+
+    /// Creates a [`Timer`] from a snapshot taken with [`Timer::snapshot`] that reads the current
+    /// time from `time_source`.
+    ///
+    /// A timer that was running when the snapshot was taken keeps running, with the wall-clock
+    /// time that passed since then counted as elapsed.
+    #[must_use]
+    pub fn restore_with_time_source(snapshot: TimerSnapshot, time_source: T) -> Timer<T> {
+        let mut elapsed_time = snapshot.elapsed;
+        if snapshot.state == TimerState::Running {
+            elapsed_time += snapshot::time_since(snapshot.saved_at, time_source.wall_clock());
+        }
+        Timer {
+            start_time: time_source.now(),
+            wall_start_time: snapshot.started_at,
+            elapsed_time,
+            duration: snapshot.duration,
+            state: snapshot.state,
+            completion_policy: snapshot.completion_policy,
+            completed_runs: snapshot.completed_runs,
+            expiry_counted: snapshot.expiry_counted,
+            past_completions: snapshot.past_completions,
+            warnings: snapshot.warnings,
+            time_source,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Timer;
+    use super::{CompletionPolicy, Timer, TimerState, WarningThreshold};
+    use crate::duration::SignedDuration;
+    use crate::error::ClockError;
+    use crate::time_source::{MockTimeSource, TimeSource};
 
     use std::sync::{Arc, Barrier, Mutex};
-    use std::thread::{self, sleep};
+    use std::thread;
     use std::time::Duration;
 
+    fn mock_timer(duration: Duration) -> (Timer<MockTimeSource>, MockTimeSource) {
+        let clock = MockTimeSource::new();
+        (Timer::with_time_source(duration, clock.clone()), clock)
+    }
+
     #[test]
     fn test_new_timer() {
         let duration = Duration::from_secs(10);
-        let mut timer = Timer::new(duration);
+        let timer = Timer::new(duration);
         assert_eq!(timer.elapsed(), Duration::default());
         assert_eq!(timer.duration(), duration);
     }
@@ -286,34 +1160,45 @@ mod tests {
     #[test]
     fn test_start_and_stop_timer() {
         let duration = Duration::from_secs(2);
-        let mut timer = Timer::new(duration);
+        let (mut timer, clock) = mock_timer(duration);
         timer.start();
-        thread::sleep(Duration::from_secs(1));
+        clock.advance(Duration::from_secs(1));
         timer.stop();
         let elapsed = timer.elapsed();
-        assert!(elapsed > Duration::from_secs(0));
+        assert_eq!(elapsed, Duration::from_secs(1));
         assert!(elapsed < duration);
         assert!(timer.remaining() < duration);
     }
 
+    #[test]
+    fn test_stopped_timer_does_not_advance() {
+        let (mut timer, clock) = mock_timer(Duration::from_secs(10));
+        timer.start();
+        clock.advance(Duration::from_secs(1));
+        timer.stop();
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(timer.elapsed(), Duration::from_secs(1));
+        assert_eq!(timer.remaining(), Duration::from_secs(9));
+    }
+
     #[test]
     fn test_remaining_start() {
         let duration = Duration::from_secs(10);
-        let mut timer = Timer::new(duration);
+        let (mut timer, clock) = mock_timer(duration);
         timer.start();
-        thread::sleep(Duration::from_secs(3));
+        clock.advance(Duration::from_secs(3));
         timer.stop();
         timer.start();
-        dbg!(timer.remaining());
+        clock.advance(Duration::from_millis(1));
         assert!(timer.remaining() < Duration::from_secs(7));
     }
 
     #[test]
     fn test_reset_timer() {
         let duration = Duration::from_secs(2);
-        let mut timer = Timer::new(duration);
+        let (mut timer, clock) = mock_timer(duration);
         timer.start();
-        thread::sleep(Duration::from_secs(1));
+        clock.advance(Duration::from_secs(1));
         timer.reset();
         assert_eq!(timer.elapsed(), Duration::default());
     }
@@ -321,14 +1206,14 @@ mod tests {
     #[test]
     fn test_remaining() {
         let duration = Duration::from_secs(10);
-        let mut timer = Timer::new(duration);
+        let (mut timer, clock) = mock_timer(duration);
 
         // Before starting the timer, remaining time should equal the duration
         assert_eq!(timer.remaining(), duration);
 
         // After starting the timer, remaining time should be less than the duration
         timer.start();
-        sleep(Duration::from_millis(50));
+        clock.advance(Duration::from_millis(50));
         assert!(timer.remaining() < duration);
 
         // After stopping the timer, remaining time should be equal to the duration minus the elapsed time
@@ -347,7 +1232,8 @@ mod tests {
         const NUM_THREADS: usize = 10;
 
         // Create a timer with a duration of 1 second
-        let timer = Arc::new(Mutex::new(Timer::new(Duration::from_secs(1))));
+        let (timer, clock) = mock_timer(Duration::from_secs(1));
+        let timer = Arc::new(Mutex::new(timer));
 
         // Create a barrier to synchronize the start of all threads
         let barrier = Arc::new(Barrier::new(NUM_THREADS));
@@ -359,6 +1245,7 @@ mod tests {
         for _ in 0..NUM_THREADS {
             let timer = timer.clone();
             let barrier = barrier.clone();
+            let clock = clock.clone();
 
             let thread = thread::spawn(move || {
                 // Wait for all threads to start at the same time
@@ -367,9 +1254,9 @@ mod tests {
                 // Repeatedly start and stop the timer until it expires
                 while timer.lock().unwrap().remaining() > Duration::from_millis(0) {
                     timer.lock().unwrap().start();
-                    thread::sleep(Duration::from_millis(10));
+                    clock.advance(Duration::from_millis(10));
                     timer.lock().unwrap().stop();
-                    thread::sleep(Duration::from_millis(10));
+                    clock.advance(Duration::from_millis(10));
                 }
             });
 
@@ -389,22 +1276,393 @@ mod tests {
     #[test]
     fn test_is_done() {
         let duration = Duration::from_secs(5);
-        let mut timer = Timer::new(duration);
+        let (mut timer, clock) = mock_timer(duration);
         assert!(!timer.is_done());
         timer.start();
-        std::thread::sleep(duration);
+        clock.advance(duration);
+        assert!(timer.is_done());
+    }
+
+    #[test]
+    fn test_wall_clock_changes_do_not_affect_remaining() {
+        let (mut timer, clock) = mock_timer(Duration::from_mins(1));
+        timer.start();
+        clock.advance(Duration::from_secs(10));
+        let started_at = timer.started_at().unwrap();
+
+        clock.set_wall_clock(started_at - Duration::from_hours(1));
+        assert_eq!(timer.remaining(), Duration::from_secs(50));
+
+        clock.set_wall_clock(started_at + Duration::from_hours(1));
+        assert_eq!(timer.remaining(), Duration::from_secs(50));
+        assert_eq!(timer.started_at(), Some(started_at));
+    }
+
+    #[test]
+    fn test_try_variants_report_state_errors() {
+        let (mut timer, clock) = mock_timer(Duration::from_secs(10));
+
+        assert_eq!(timer.try_stop(), Err(ClockError::NotRunning));
+        assert_eq!(timer.try_start(), Ok(()));
+        assert_eq!(timer.try_start(), Err(ClockError::AlreadyRunning));
+
+        clock.advance(Duration::from_secs(4));
+        assert_eq!(timer.try_elapsed(), Ok(Duration::from_secs(4)));
+        assert_eq!(timer.try_remaining(), Ok(Duration::from_secs(6)));
+        assert_eq!(timer.try_stop(), Ok(()));
+        assert_eq!(timer.remaining(), Duration::from_secs(6));
+    }
+
+    #[test]
+    fn test_state_transitions() {
+        let (mut timer, clock) = mock_timer(Duration::from_secs(10));
+        assert_eq!(timer.state(), TimerState::Idle);
+
+        timer.start();
+        assert_eq!(timer.state(), TimerState::Running);
+
+        timer.stop();
+        assert_eq!(timer.state(), TimerState::Paused);
+        assert!(!timer.has_not_started());
+
+        timer.start();
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(timer.state(), TimerState::Expired);
+        assert!(!timer.is_running());
+        assert_eq!(
+            timer.try_stop(),
+            Err(ClockError::InvalidTransition {
+                from: TimerState::Expired,
+                to: TimerState::Paused,
+            })
+        );
+        assert_eq!(
+            timer.try_start(),
+            Err(ClockError::InvalidTransition {
+                from: TimerState::Expired,
+                to: TimerState::Running,
+            })
+        );
+
+        timer.reset();
+        assert_eq!(timer.state(), TimerState::Idle);
+    }
+
+    #[test]
+    fn test_paused_without_elapsed_time_has_started() {
+        let (mut timer, _clock) = mock_timer(Duration::from_secs(10));
+        timer.start();
+        timer.stop();
+        assert_eq!(timer.remaining(), timer.duration());
+        assert_eq!(timer.state(), TimerState::Paused);
+        assert!(!timer.has_not_started());
+    }
+
+    #[test]
+    fn test_adjust_duration() {
+        let (mut timer, clock) = mock_timer(Duration::from_mins(1));
+
+        timer.add_time(Duration::from_secs(30));
+        assert_eq!(timer.duration(), Duration::from_secs(90));
+        assert_eq!(timer.state(), TimerState::Idle);
+
+        timer.start();
+        clock.advance(Duration::from_secs(40));
+        timer.stop();
+        timer.subtract_time(Duration::from_secs(20));
+        assert_eq!(timer.elapsed(), Duration::from_secs(40));
+        assert_eq!(timer.remaining(), Duration::from_secs(30));
+        assert_eq!(timer.state(), TimerState::Paused);
+
+        timer.subtract_time(Duration::from_mins(1));
+        assert_eq!(timer.remaining(), Duration::ZERO);
+        assert_eq!(timer.state(), TimerState::Expired);
+
+        timer.add_time(Duration::from_secs(10));
+        assert_eq!(timer.state(), TimerState::Paused);
+        timer.start();
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(timer.state(), TimerState::Expired);
+        assert_eq!(timer.elapsed(), Duration::from_secs(50));
+    }
+
+    #[test]
+    fn test_adjust_duration_after_expiry() {
+        let (mut timer, clock) = mock_timer(Duration::from_secs(10));
+        timer.start();
+        clock.advance(Duration::from_secs(25));
+        assert!(timer.is_done());
+
+        timer.add_time(Duration::from_mins(1));
+        assert!(timer.is_running());
+        assert_eq!(timer.remaining(), Duration::from_mins(1));
+
+        timer.set_duration(Duration::from_secs(5));
         assert!(timer.is_done());
+
+        timer.reset();
+        assert_eq!(timer.remaining(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_elapsed_stops_at_expiry_without_overtime() {
+        let (mut timer, clock) = mock_timer(Duration::from_secs(10));
+        timer.start();
+        clock.advance(Duration::from_secs(15));
+
+        assert_eq!(timer.elapsed(), Duration::from_secs(10));
+        assert_eq!(timer.signed_remaining(), SignedDuration::ZERO);
+        assert_eq!(timer.overtime(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_overtime() {
+        let (mut timer, clock) = mock_timer(Duration::from_secs(10));
+        timer.set_overtime(true);
+        timer.start();
+        clock.advance(Duration::from_secs(4));
+        assert_eq!(
+            timer.signed_remaining(),
+            SignedDuration::positive(Duration::from_secs(6))
+        );
+
+        clock.advance(Duration::from_secs(48));
+        assert_eq!(timer.state(), TimerState::Expired);
+        assert_eq!(timer.remaining(), Duration::ZERO);
+        assert_eq!(timer.elapsed(), Duration::from_secs(52));
+        assert_eq!(timer.overtime(), Duration::from_secs(42));
+        assert_eq!(
+            timer.signed_remaining(),
+            SignedDuration::negative(Duration::from_secs(42))
+        );
+
+        timer.set_overtime(false);
+        assert_eq!(timer.elapsed(), Duration::from_secs(10));
+        clock.advance(Duration::from_secs(5));
+        timer.add_time(Duration::from_secs(30));
+        assert_eq!(timer.remaining(), Duration::from_secs(30));
+        assert_eq!(timer.state(), TimerState::Running);
+    }
+
+    #[test]
+    fn test_auto_reset() {
+        let (mut timer, clock) = mock_timer(Duration::from_secs(10));
+        timer.set_completion_policy(CompletionPolicy::AutoReset);
+        timer.start();
+        clock.advance(Duration::from_secs(12));
+
+        assert_eq!(timer.state(), TimerState::Idle);
+        assert_eq!(timer.remaining(), Duration::from_secs(10));
+        assert_eq!(timer.completions(), 1);
+
+        timer.start();
+        clock.advance(Duration::from_secs(4));
+        assert_eq!(timer.state(), TimerState::Running);
+        assert_eq!(timer.remaining(), Duration::from_secs(6));
+        assert_eq!(timer.completions(), 1);
+
+        clock.advance(Duration::from_secs(6));
+        assert_eq!(timer.completions(), 2);
+        timer.reset();
+        assert_eq!(timer.completions(), 2);
+    }
+
+    #[test]
+    fn test_repeat_is_anchored_to_start() {
+        let (mut timer, clock) = mock_timer(Duration::from_secs(10));
+        timer.set_completion_policy(CompletionPolicy::Repeat(2));
+        timer.start();
+
+        clock.advance(Duration::from_secs(25));
+        assert_eq!(timer.state(), TimerState::Running);
+        assert_eq!(timer.elapsed(), Duration::from_secs(5));
+        assert_eq!(timer.completions(), 2);
+
+        timer.stop();
+        clock.advance(Duration::from_mins(1));
+        timer.start();
+        clock.advance(Duration::from_secs(4));
+        assert_eq!(timer.remaining(), Duration::from_secs(1));
+
+        clock.advance(Duration::from_secs(20));
+        assert_eq!(timer.state(), TimerState::Expired);
+        assert_eq!(timer.elapsed(), Duration::from_secs(10));
+        assert_eq!(timer.completions(), 3);
+
+        timer.reset();
+        timer.start();
+        clock.advance(Duration::from_secs(15));
+        assert_eq!(timer.state(), TimerState::Running);
+        assert_eq!(timer.completions(), 4);
+    }
+
+    #[test]
+    fn test_repeat_forever_does_not_drift() {
+        let (mut timer, clock) = mock_timer(Duration::from_mins(20));
+        timer.set_completion_policy(CompletionPolicy::RepeatForever);
+        timer.start();
+
+        for _ in 0..(6 * 60 * 60) {
+            clock.advance(Duration::from_millis(1001));
+            let _ = timer.state();
+        }
+        // 6 hours and 21.6 seconds.
+        assert_eq!(timer.cycle(), 18);
+        assert_eq!(timer.remaining(), Duration::from_millis(1_178_400));
+        assert_eq!(timer.completions(), 18);
+
+        timer.stop();
+        clock.advance(Duration::from_hours(1));
+        timer.start();
+        assert_eq!(timer.cycle(), 18);
+        assert_eq!(timer.remaining(), Duration::from_millis(1_178_400));
+        assert_eq!(timer.remaining_cycles(), None);
+    }
+
+    #[test]
+    fn test_remaining_cycles() {
+        let (mut timer, clock) = mock_timer(Duration::from_secs(10));
+        assert_eq!(timer.remaining_cycles(), Some(0));
+
+        timer.set_completion_policy(CompletionPolicy::Repeat(3));
+        timer.start();
+        assert_eq!((timer.cycle(), timer.remaining_cycles()), (0, Some(3)));
+        clock.advance(Duration::from_secs(25));
+        assert_eq!((timer.cycle(), timer.remaining_cycles()), (2, Some(1)));
+        clock.advance(Duration::from_secs(25));
+        assert_eq!(timer.state(), TimerState::Expired);
+        assert_eq!((timer.cycle(), timer.remaining_cycles()), (3, Some(0)));
+
+        timer.reset();
+        assert_eq!((timer.cycle(), timer.remaining_cycles()), (0, Some(3)));
+    }
+
+    #[test]
+    fn test_warnings_fire_once() {
+        let (mut timer, clock) = mock_timer(Duration::from_mins(10));
+        timer.add_warning(WarningThreshold::Remaining(Duration::from_mins(5)));
+        timer.add_warning(WarningThreshold::Remaining(Duration::from_secs(30)));
+        timer.add_warning(WarningThreshold::Remaining(Duration::from_mins(20)));
+        timer.start();
+        assert_eq!(timer.next_warning(), Some(Duration::from_mins(5)));
+
+        clock.advance(Duration::from_mins(4));
+        assert!(timer.take_warnings().is_empty());
+        timer.stop();
+        clock.advance(Duration::from_hours(1));
+        assert!(timer.take_warnings().is_empty());
+        timer.start();
+
+        clock.advance(Duration::from_mins(1));
+        assert_eq!(timer.take_warnings(), vec![0]);
+        timer.stop();
+        timer.start();
+        assert!(timer.take_warnings().is_empty());
+        assert_eq!(
+            timer.next_warning(),
+            Some(Duration::from_mins(4) + Duration::from_secs(30))
+        );
+
+        clock.advance(Duration::from_mins(6));
+        assert!(timer.take_warnings().is_empty());
+        assert_eq!(timer.state(), TimerState::Expired);
+    }
+
+    #[test]
+    fn test_warnings_follow_duration_changes() {
+        let (mut timer, clock) = mock_timer(Duration::from_mins(10));
+        timer.add_warning(WarningThreshold::Remaining(Duration::from_mins(5)));
+        timer.add_warning(WarningThreshold::PercentRemaining(20));
+        timer.start();
+
+        clock.advance(Duration::from_mins(6));
+        assert_eq!(timer.take_warnings(), vec![0]);
+
+        timer.add_time(Duration::from_mins(2));
+        assert!(timer.take_warnings().is_empty());
+        clock.advance(Duration::from_mins(1));
+        assert_eq!(timer.take_warnings(), vec![0]);
+
+        timer.subtract_time(Duration::from_mins(4));
+        assert_eq!(timer.take_warnings(), vec![1]);
+
+        timer.reset();
+        timer.start();
+        clock.advance(Duration::from_mins(7));
+        assert_eq!(timer.take_warnings(), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_stay_expired_counts_one_completion() {
+        let (mut timer, clock) = mock_timer(Duration::from_secs(10));
+        timer.start();
+        clock.advance(Duration::from_secs(30));
+        timer.stop();
+        timer.set_duration(Duration::from_secs(5));
+        assert_eq!(timer.completions(), 1);
+        timer.add_time(Duration::from_secs(5));
+        assert_eq!(timer.completions(), 1);
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(timer.completions(), 2);
+
+        timer.set_overtime(true);
+        timer.add_time(Duration::from_secs(5));
+        assert_eq!(timer.completions(), 2);
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(timer.completions(), 3);
+        assert_eq!(timer.overtime(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_started_at() {
+        let (mut timer, clock) = mock_timer(Duration::from_mins(1));
+        assert_eq!(timer.started_at(), None);
+        timer.start();
+        assert_eq!(timer.started_at(), Some(clock.wall_clock()));
+        timer.reset();
+        assert_eq!(timer.started_at(), None);
     }
 
     #[test]
     fn test_has_not_started() {
         let duration = Duration::from_secs(10);
-        let mut timer = Timer::new(duration);
+        let (mut timer, clock) = mock_timer(duration);
         assert!(timer.has_not_started());
         timer.start();
-        sleep(Duration::from_secs(1));
+        clock.advance(Duration::from_secs(1));
         assert!(!timer.has_not_started());
-        std::thread::sleep(Duration::from_secs(5));
+        clock.advance(Duration::from_secs(5));
         assert!(!timer.has_not_started());
     }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let (mut timer, clock) = mock_timer(Duration::from_secs(30));
+        timer.set_completion_policy(CompletionPolicy::Repeat(2));
+        timer.add_warning(WarningThreshold::Remaining(Duration::from_secs(10)));
+        timer.start();
+        clock.advance(Duration::from_secs(50));
+        assert_eq!(timer.take_warnings(), vec![0]);
+
+        let snapshot = timer.snapshot();
+        clock.advance(Duration::from_secs(5));
+        let mut restored = Timer::restore_with_time_source(snapshot.clone(), clock.clone());
+        assert_eq!(restored.state(), TimerState::Running);
+        assert_eq!(restored.completions(), 1);
+        assert_eq!(restored.remaining(), Duration::from_secs(5));
+        assert_eq!(restored.started_at(), timer.started_at());
+        assert!(restored.take_warnings().is_empty());
+
+        // A wall clock set back while the process was not running adds no time.
+        clock.set_wall_clock(snapshot.saved_at() - Duration::from_mins(1));
+        let restored = Timer::restore_with_time_source(snapshot, clock.clone());
+        assert_eq!(restored.remaining(), Duration::from_secs(10));
+
+        timer.stop();
+        let snapshot = timer.snapshot();
+        clock.advance(Duration::from_mins(1));
+        let restored = Timer::restore_with_time_source(snapshot, clock.clone());
+        assert_eq!(restored.state(), TimerState::Paused);
+        assert_eq!(restored.remaining(), timer.remaining());
+    }
 }
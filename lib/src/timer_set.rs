@@ -0,0 +1,420 @@
+//! A module that defines a collection of labelled timers
+//!
+//! A [`TimerSet`] owns any number of [`Timer`]s, each with a label and a [`TimerId`] that stays
+//! the same for as long as the timer is in the set, so front-ends do not have to keep their own
+//! collection in sync with the timers.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::snapshot::{TimerSetEntrySnapshot, TimerSetSnapshot};
+use crate::time_source::{SystemTimeSource, TimeSource};
+use crate::timer::{Timer, TimerState};
+
+/// The identifier of a timer in a [`TimerSet`].
+///
+/// Identifiers are never reused by the set that handed them out, even after the timer is removed.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimerId(u64);
+
+impl TimerId {
+    /// Returns the identifier as a number, e.g. to hand it to another language.
+    #[must_use]
+    pub fn as_u64(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for TimerId {
+    fn from(id: u64) -> Self {
+        TimerId(id)
+    }
+}
+
+impl fmt::Display for TimerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&format!("#{}", self.0))
+    }
+}
+
+impl FromStr for TimerId {
+    type Err = ParseIntError;
+
+    /// Parses an id as displayed, e.g. `#3`, or as a plain number.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.strip_prefix('#').unwrap_or(s).parse().map(TimerId)
+    }
+}
+
+/// A timer in a [`TimerSet`] along with its label.
+#[derive(Debug, Clone)]
+struct Entry<T: TimeSource> {
+    /// The label shown for the timer.
+    label: String,
+    /// The timer itself.
+    timer: Timer<T>,
+    /// The completions of the timer that were already returned by [`TimerSet::take_expired`].
+    reported_completions: u32,
+}
+
+/// A collection of labelled timers keyed by [`TimerId`].
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use clock::time_source::MockTimeSource;
+/// use clock::timer_set::TimerSet;
+///
+/// let clock = MockTimeSource::new();
+/// let mut timers = TimerSet::with_time_source(clock.clone());
+/// let pasta = timers.add("Pasta", Duration::from_secs(9 * 60));
+/// let eggs = timers.add("Eggs", Duration::from_secs(6 * 60));
+///
+/// timers.start_all();
+/// assert_eq!(timers.by_next_expiry(), vec![eggs, pasta]);
+///
+/// clock.advance(Duration::from_secs(7 * 60));
+/// assert_eq!(timers.take_expired(), vec![eggs]);
+/// assert_eq!(timers.take_expired(), vec![]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TimerSet<T: TimeSource + Clone = SystemTimeSource> {
+    /// The timers, ordered by id.
+    entries: BTreeMap<TimerId, Entry<T>>,
+    /// The id handed to the next timer that is added.
+    next_id: u64,
+    /// The [`TimeSource`] given to the timers created by [`TimerSet::add`].
+    time_source: T,
+}
+
+impl TimerSet {
+    /// Creates an empty [`TimerSet`] whose timers read the system clock.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_time_source(SystemTimeSource)
+    }
+}
+
+impl TimerSet {
+    /// Creates a [`TimerSet`] that reads the system clock from a snapshot taken with
+    /// [`TimerSet::snapshot`], see [`TimerSet::restore_with_time_source`].
+    #[must_use]
+    pub fn restore(snapshot: TimerSetSnapshot) -> Self {
+        Self::restore_with_time_source(snapshot, SystemTimeSource)
+    }
+}
+
+impl Default for TimerSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: TimeSource + Clone> TimerSet<T> {
+    /// Creates an empty [`TimerSet`] whose timers read the current time from `time_source`.
+    #[must_use]
+    pub fn with_time_source(time_source: T) -> Self {
+        Self {
+            entries: BTreeMap::new(),
+            next_id: 0,
+            time_source,
+        }
+    }
+
+    /// Adds a new timer of `duration` and returns its id.
+    pub fn add(&mut self, label: impl Into<String>, duration: Duration) -> TimerId {
+        let timer = Timer::with_time_source(duration, self.time_source.clone());
+        self.insert(label, timer)
+    }
+
+    /// Adds an existing timer, e.g. one configured with a completion policy, and returns its id.
+    pub fn insert(&mut self, label: impl Into<String>, timer: Timer<T>) -> TimerId {
+        let id = TimerId(self.next_id);
+        self.next_id += 1;
+        self.entries.insert(
+            id,
+            Entry {
+                label: label.into(),
+                reported_completions: timer.completions(),
+                timer,
+            },
+        );
+        id
+    }
+
+    /// Removes the timer with the given id and returns it, or `None` if there is no such timer.
+    pub fn remove(&mut self, id: TimerId) -> Option<Timer<T>> {
+        self.entries.remove(&id).map(|entry| entry.timer)
+    }
+
+    /// Returns the timer with the given id.
+    #[must_use]
+    pub fn get(&self, id: TimerId) -> Option<&Timer<T>> {
+        self.entries.get(&id).map(|entry| &entry.timer)
+    }
+
+    /// Returns the timer with the given id to change it.
+    pub fn get_mut(&mut self, id: TimerId) -> Option<&mut Timer<T>> {
+        self.entries.get_mut(&id).map(|entry| &mut entry.timer)
+    }
+
+    /// Returns the label of the timer with the given id.
+    #[must_use]
+    pub fn label(&self, id: TimerId) -> Option<&str> {
+        self.entries.get(&id).map(|entry| entry.label.as_str())
+    }
+
+    /// Changes the label of the timer with the given id. Returns `false` if there is no such timer.
+    pub fn set_label(&mut self, id: TimerId, label: impl Into<String>) -> bool {
+        match self.entries.get_mut(&id) {
+            Some(entry) => {
+                entry.label = label.into();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the number of timers in the set.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the set has no timers.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the id, label and timer of every timer in the set, ordered by id.
+    pub fn iter(&self) -> impl Iterator<Item = (TimerId, &str, &Timer<T>)> {
+        self.entries
+            .iter()
+            .map(|(&id, entry)| (id, entry.label.as_str(), &entry.timer))
+    }
+
+    /// Starts every timer that has not been started yet and resumes every paused one.
+    pub fn start_all(&mut self) {
+        for entry in self.entries.values_mut() {
+            entry.timer.start();
+        }
+    }
+
+    /// Pauses every running timer.
+    pub fn pause_all(&mut self) {
+        for entry in self.entries.values_mut() {
+            entry.timer.stop();
+        }
+    }
+
+    /// Resumes every paused timer, leaving the ones that were never started alone.
+    pub fn resume_all(&mut self) {
+        for entry in self.entries.values_mut() {
+            if entry.timer.state() == TimerState::Paused {
+                entry.timer.start();
+            }
+        }
+    }
+
+    /// Returns the ids of all timers, the one that expires next first.
+    ///
+    /// Running timers come first, ordered by remaining time, followed by paused timers, timers
+    /// that were never started and finally expired timers.
+    #[must_use]
+    pub fn by_next_expiry(&self) -> Vec<TimerId> {
+        let mut ids: Vec<_> = self
+            .entries
+            .iter()
+            .map(|(&id, entry)| {
+                let rank = match entry.timer.state() {
+                    TimerState::Running => 0,
+                    TimerState::Paused => 1,
+                    TimerState::Idle => 2,
+                    TimerState::Expired => 3,
+                };
+                ((rank, entry.timer.remaining()), id)
+            })
+            .collect();
+        ids.sort();
+        ids.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// Returns a snapshot of the set that can be persisted and turned back into a set with
+    /// [`TimerSet::restore_with_time_source`], e.g. after the process restarts.
+    #[must_use]
+    pub fn snapshot(&self) -> TimerSetSnapshot {
+        TimerSetSnapshot {
+            entries: self
+                .entries
+                .iter()
+                .map(|(&id, entry)| TimerSetEntrySnapshot {
+                    id,
+                    label: entry.label.clone(),
+                    timer: entry.timer.snapshot(),
+                    reported_completions: entry.reported_completions,
+                })
+                .collect(),
+            next_id: self.next_id,
+        }
+    }
+
+    /// Creates a [`TimerSet`] whose timers read the current time from `time_source` from a
+    /// snapshot taken with [`TimerSet::snapshot`].
+    ///
+    /// The timers keep their ids, and timers that ran out of time while the set was not running
+    /// are returned by the next call to [`TimerSet::take_expired`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use clock::time_source::MockTimeSource;
+    /// use clock::timer_set::TimerSet;
+    ///
+    /// let clock = MockTimeSource::new();
+    /// let mut timers = TimerSet::with_time_source(clock.clone());
+    /// let tea = timers.add("Tea", Duration::from_secs(3 * 60));
+    /// let eggs = timers.add("Eggs", Duration::from_secs(6 * 60));
+    /// timers.remove(tea);
+    /// timers.start_all();
+    /// let snapshot = timers.snapshot();
+    ///
+    /// clock.advance(Duration::from_secs(7 * 60));
+    ///
+    /// let mut timers = TimerSet::restore_with_time_source(snapshot, clock.clone());
+    /// assert_eq!(timers.label(eggs), Some("Eggs"));
+    /// assert_eq!(timers.take_expired(), vec![eggs]);
+    /// assert_ne!(timers.add("Tea", Duration::from_secs(3 * 60)), tea);
+    /// ```
+    #[must_use]
+    pub fn restore_with_time_source(snapshot: TimerSetSnapshot, time_source: T) -> Self {
+        let entries = snapshot
+            .entries
+            .into_iter()
+            .map(|entry| {
+                let timer = Timer::restore_with_time_source(entry.timer, time_source.clone());
+                (
+                    entry.id,
+                    Entry {
+                        label: entry.label,
+                        timer,
+                        reported_completions: entry.reported_completions,
+                    },
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        let next_id = entries
+            .keys()
+            .next_back()
+            .map_or(0, |id| id.0.saturating_add(1))
+            .max(snapshot.next_id);
+        Self {
+            entries,
+            next_id,
+            time_source,
+        }
+    }
+
+    /// Returns the ids of the timers that ran out of time since the last call, ordered by id.
+    ///
+    /// A timer is returned once for every call in which it completed at least one run, see
+    /// [`Timer::completions`].
+    pub fn take_expired(&mut self) -> Vec<TimerId> {
+        self.entries
+            .iter_mut()
+            .filter_map(|(&id, entry)| {
+                let completions = entry.timer.completions();
+                let expired = completions > entry.reported_completions;
+                entry.reported_completions = completions;
+                expired.then_some(id)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TimerId, TimerSet};
+    use crate::time_source::MockTimeSource;
+    use crate::timer::{CompletionPolicy, Timer, TimerState};
+
+    use std::time::Duration;
+
+    #[test]
+    fn test_ids_labels_and_removal() {
+        let mut timers = TimerSet::with_time_source(MockTimeSource::new());
+        let tea = timers.add("Tea", Duration::from_mins(3));
+        let bread = timers.add("Bread", Duration::from_mins(40));
+
+        assert_eq!(timers.len(), 2);
+        assert_eq!(timers.label(tea), Some("Tea"));
+        assert!(timers.set_label(bread, "Sourdough"));
+        assert_eq!(timers.label(bread), Some("Sourdough"));
+
+        assert!(timers.remove(tea).is_some());
+        assert!(timers.remove(tea).is_none());
+        assert!(!timers.set_label(tea, "Tea"));
+
+        let coffee = timers.add("Coffee", Duration::from_mins(4));
+        assert_ne!(coffee, tea);
+        assert_eq!(
+            timers.iter().map(|(id, _, _)| id).collect::<Vec<_>>(),
+            vec![bread, coffee]
+        );
+        assert_eq!(TimerId::from(coffee.as_u64()), coffee);
+        assert_eq!(coffee.to_string().parse(), Ok(coffee));
+    }
+
+    #[test]
+    fn test_pause_and_resume_all() {
+        let clock = MockTimeSource::new();
+        let mut timers = TimerSet::with_time_source(clock.clone());
+        let first = timers.add("First", Duration::from_mins(5));
+        let second = timers.add("Second", Duration::from_mins(5));
+        timers.get_mut(first).unwrap().start();
+
+        timers.pause_all();
+        assert_eq!(timers.get(first).unwrap().state(), TimerState::Paused);
+        clock.advance(Duration::from_mins(1));
+
+        timers.resume_all();
+        assert_eq!(timers.get(first).unwrap().state(), TimerState::Running);
+        assert_eq!(timers.get(second).unwrap().state(), TimerState::Idle);
+    }
+
+    #[test]
+    fn test_next_expiry_and_expired() {
+        let clock = MockTimeSource::new();
+        let mut timers = TimerSet::with_time_source(clock.clone());
+        let idle = timers.add("Idle", Duration::from_secs(1));
+        let long = timers.add("Long", Duration::from_mins(10));
+        let short = timers.add("Short", Duration::from_mins(2));
+        let mut repeating = Timer::with_time_source(Duration::from_secs(50), clock.clone());
+        repeating.set_completion_policy(CompletionPolicy::RepeatForever);
+        let repeating = timers.insert("Repeating", repeating);
+        for id in [long, short, repeating] {
+            timers.get_mut(id).unwrap().start();
+        }
+
+        assert_eq!(timers.by_next_expiry(), vec![repeating, short, long, idle]);
+
+        clock.advance(Duration::from_secs(90));
+        assert_eq!(timers.take_expired(), vec![repeating]);
+        assert_eq!(timers.by_next_expiry(), vec![repeating, short, long, idle]);
+
+        clock.advance(Duration::from_mins(1));
+        assert_eq!(timers.take_expired(), vec![short, repeating]);
+        assert_eq!(timers.by_next_expiry(), vec![repeating, long, idle, short]);
+        assert!(timers.take_expired().is_empty());
+    }
+}
@@ -9,13 +9,34 @@ import SwiftUI
 
 struct LapTimeTable: View {
   var laptimes: [LapTime]
+  var fastestLap: Int
+  var slowestLap: Int
 
   init() {
     self.laptimes = []
+    self.fastestLap = 0
+    self.slowestLap = 0
   }
 
-  init(laptimes: LapTimeVec) {
-    self.laptimes = laptimes.asArray()
+  init(stopwatch: Stopwatch) {
+    self.laptimes = stopwatch.lapTimes.asArray()
+    self.fastestLap = stopwatch.fastestLap
+    self.slowestLap = stopwatch.slowestLap
+  }
+
+  /// Highlights the best and worst laps once there are at least two to compare.
+  func color(of lapTime: LapTime) -> Color {
+    if laptimes.count < 2 {
+      return .primary
+    }
+    switch lapTime.lapNumber {
+    case fastestLap:
+      return .green
+    case slowestLap:
+      return .red
+    default:
+      return .primary
+    }
   }
 
   var body: some View {
@@ -23,6 +44,7 @@ struct LapTimeTable: View {
       TableColumn("Lap No.", value: \LapTime.lapNumber.description)
       TableColumn("Split Time") { lapTime in
         Text(Stopwatch.formatTimeString(duration: lapTime.splitTime))
+          .foregroundColor(color(of: lapTime))
       }
       TableColumn("Total Time") { lapTime in
         Text(Stopwatch.formatTimeString(duration: lapTime.totalTime))
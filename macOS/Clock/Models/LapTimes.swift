@@ -22,6 +22,19 @@ class LapTime: Identifiable {
     RDuration(value: stopwatch_LapTime_totalTime(ptr))
   }
 
+  var label: String? {
+    LapTime.takeString(stopwatch_LapTime_label(ptr))
+  }
+
+  var note: String? {
+    LapTime.takeString(stopwatch_LapTime_note(ptr))
+  }
+
+  /// The time of day the lap was taken in ISO 8601 format.
+  var recordedAt: String? {
+    LapTime.takeString(stopwatch_LapTime_recordedAt(ptr))
+  }
+
   var id: Int {
     lapNumber
   }
@@ -30,6 +43,13 @@ class LapTime: Identifiable {
     self.ptr = ptr
   }
 
+  /// Copies a string returned by the library and frees the original.
+  private static func takeString(_ cString: UnsafeMutablePointer<CChar>?) -> String? {
+    guard let cString else { return nil }
+    defer { rust_String_free(cString) }
+    return String(cString: cString)
+  }
+
   deinit {
     stopwatch_LapTime_free(ptr)
   }
@@ -14,6 +14,14 @@ public class Stopwatch {
     self.ptr = stopwatch_Stopwatch_create()
   }
 
+  /// Restores a stopwatch saved with `save(forKey:)`, or returns nil if there is none.
+  init?(restoringFrom key: String) {
+    guard let json = UserDefaults.standard.string(forKey: key),
+      let ptr = stopwatch_Stopwatch_restoreJson(json)
+    else { return nil }
+    self.ptr = ptr
+  }
+
   var isRunning: Bool {
     stopwatch_Stopwatch_isRunning(ptr)
   }
@@ -26,10 +34,51 @@ public class Stopwatch {
     RDuration(value: stopwatch_Stopwatch_elapsedTime(ptr))
   }
 
+  /// The time the stopwatch spent stopped since it was first started.
+  var pausedTime: RDuration {
+    RDuration(value: stopwatch_Stopwatch_pausedTime(ptr))
+  }
+
+  /// The session, with its segments and laps, as CSV.
+  var csv: String {
+    Stopwatch.takeString(stopwatch_Stopwatch_exportCsv(ptr))
+  }
+
+  /// The session, with its segments and laps, as Markdown tables.
+  var markdown: String {
+    Stopwatch.takeString(stopwatch_Stopwatch_exportMarkdown(ptr))
+  }
+
+  /// The number of the fastest lap, or 0 if there are no laps.
+  var fastestLap: Int {
+    stopwatch_Stopwatch_fastestLap(ptr)
+  }
+
+  /// The number of the slowest lap, or 0 if there are no laps.
+  var slowestLap: Int {
+    stopwatch_Stopwatch_slowestLap(ptr)
+  }
+
   func start() {
     stopwatch_Stopwatch_start(ptr)
   }
 
+  /// Starts the stopwatch once `countdown` seconds have passed, with its zero at the end of the
+  /// countdown.
+  func start(after countdown: TimeInterval) {
+    stopwatch_Stopwatch_startAfter(ptr, countdown)
+  }
+
+  /// The time left until a delayed start, or nil if the stopwatch is not counting down.
+  var countdownRemaining: RDuration? {
+    stopwatch_Stopwatch_countdownRemaining(ptr).map { RDuration(value: $0) }
+  }
+
+  @discardableResult
+  func cancelCountdown() -> Bool {
+    stopwatch_Stopwatch_cancelCountdown(ptr)
+  }
+
   func stop() {
     stopwatch_Stopwatch_stop(ptr)
   }
@@ -42,10 +91,45 @@ public class Stopwatch {
     stopwatch_Stopwatch_addLap(ptr)
   }
 
+  @discardableResult
+  func removeLap(_ lapNumber: Int) -> Bool {
+    stopwatch_Stopwatch_removeLap(ptr, lapNumber)
+  }
+
+  @discardableResult
+  func insertLap(at totalTime: TimeInterval) -> Bool {
+    stopwatch_Stopwatch_insertLap(ptr, totalTime)
+  }
+
+  @discardableResult
+  func setLabel(_ label: String?, ofLap lapNumber: Int) -> Bool {
+    guard let label else { return stopwatch_Stopwatch_setLapLabel(ptr, lapNumber, nil) }
+    return stopwatch_Stopwatch_setLapLabel(ptr, lapNumber, label)
+  }
+
+  @discardableResult
+  func setNote(_ note: String?, ofLap lapNumber: Int) -> Bool {
+    guard let note else { return stopwatch_Stopwatch_setLapNote(ptr, lapNumber, nil) }
+    return stopwatch_Stopwatch_setLapNote(ptr, lapNumber, note)
+  }
+
   static func formatTimeString(duration: RDuration) -> String {
     String.init(cString: stopwatch_formatTime(duration.ptr))
   }
 
+  /// Saves the stopwatch, with its laps, so that `init?(restoringFrom:)` can bring it back after
+  /// the app restarts.
+  func save(forKey key: String) {
+    guard let json = stopwatch_Stopwatch_snapshotJson(ptr) else { return }
+    UserDefaults.standard.set(Stopwatch.takeString(json), forKey: key)
+  }
+
+  /// Copies a string returned by the library and frees the original.
+  private static func takeString(_ cString: UnsafeMutablePointer<CChar>) -> String {
+    defer { rust_String_free(cString) }
+    return String(cString: cString)
+  }
+
   deinit {
     stopwatch_Stopwatch_free(ptr)
   }
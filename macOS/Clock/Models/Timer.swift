@@ -8,54 +8,162 @@
 import Foundation
 import UserNotifications
 
+/// A timer owned by a `TimerDriver`, whose background thread reports what happens to the timer
+/// through `onEvent` so that views do not have to poll it.
 public class RTimer: Equatable {
   public static func == (lhs: RTimer, rhs: RTimer) -> Bool {
-      timer_Timer_eq(lhs.ptr, rhs.ptr)
+    lhs.withTimer { timer in rhs.withTimer { other in timer_Timer_eq(timer, other) } }
   }
-    
+
+  /// The driver that owns the timer.
   var ptr: UnsafeMutableRawPointer
+  private let eventHandler = EventHandler()
 
   init(secs: UInt64) {
-    self.ptr = timer_Timer_create(secs)
+    self.ptr = driver_TimerDriver_create(secs)
+    registerEventHandler()
+  }
+
+  /// Restores a timer saved with `save(forKey:)`, or returns nil if there is none.
+  init?(restoringFrom key: String) {
+    guard let json = UserDefaults.standard.string(forKey: key),
+      let timer = timer_Timer_restoreJson(json)
+    else { return nil }
+    self.ptr = driver_TimerDriver_fromTimer(timer)
+    registerEventHandler()
+  }
+
+  /// Called on a background thread for every event of the timer, e.g. `TimerEvent_Expired`.
+  var onEvent: ((TimerEvent) -> Void)? {
+    get { eventHandler.handle }
+    set { eventHandler.handle = newValue }
+  }
+
+  var state: TimerState {
+    driver_TimerDriver_state(ptr)
   }
 
   var isRunning: Bool {
-    timer_Timer_isRunning(ptr)
+    withTimer { timer_Timer_isRunning($0) }
   }
 
   var isDone: Bool {
-    timer_Timer_isDone(ptr)
+    withTimer { timer_Timer_isDone($0) }
   }
 
   var hasNotStarted: Bool {
-    timer_Timer_hasNotStarted(ptr)
+    withTimer { timer_Timer_hasNotStarted($0) }
+  }
+
+  var isOvertimeEnabled: Bool {
+    get { withTimer { timer_Timer_isOvertimeEnabled($0) } }
+    set { driver_TimerDriver_setOvertime(ptr, newValue) }
+  }
+
+  var completionPolicy: CompletionPolicy {
+    get { withTimer { timer_Timer_completionPolicy($0) } }
+    set { driver_TimerDriver_setCompletionPolicy(ptr, newValue) }
+  }
+
+  var completions: UInt32 {
+    withTimer { timer_Timer_completions($0) }
+  }
+
+  var overtime: RDuration {
+    RDuration(value: withTimer { timer_Timer_overtime($0) })
   }
 
   var elapsed: RDuration {
-    RDuration(value: timer_Timer_elapsed(ptr))
+    RDuration(value: withTimer { timer_Timer_elapsed($0) })
   }
 
   var duration: RDuration {
-    RDuration(value: timer_Timer_duration(ptr))
+    RDuration(value: withTimer { timer_Timer_duration($0) })
   }
 
   func start() {
-    timer_Timer_start(ptr)
+    driver_TimerDriver_start(ptr)
   }
 
   func stop() {
-    timer_Timer_stop(ptr)
+    driver_TimerDriver_stop(ptr)
   }
 
   func reset() {
-    timer_Timer_reset(ptr)
+    driver_TimerDriver_reset(ptr)
+  }
+
+  func addTime(secs: UInt64) {
+    driver_TimerDriver_addTime(ptr, secs)
+  }
+
+  func subtractTime(secs: UInt64) {
+    driver_TimerDriver_subtractTime(ptr, secs)
+  }
+
+  func setDuration(secs: UInt64) {
+    driver_TimerDriver_setDuration(ptr, secs)
+  }
+
+  func setRemaining(secs: UInt64) {
+    driver_TimerDriver_setRemaining(ptr, secs)
+  }
+
+  /// Emits `TimerEvent_Warning` once `secs` seconds remain.
+  func addWarning(secs: UInt64) {
+    driver_TimerDriver_addWarningSecs(ptr, secs)
+  }
+
+  /// Emits `TimerEvent_Warning` once `percent` percent of the duration remains.
+  func addWarning(percent: UInt8) {
+    driver_TimerDriver_addWarningPercent(ptr, percent)
+  }
+
+  func clearWarnings() {
+    driver_TimerDriver_clearWarnings(ptr)
+  }
+
+  /// The time until the next warning, or nil if the timer is not running or has none left.
+  var nextWarning: RDuration? {
+    withTimer { timer_Timer_nextWarning($0) }.map { RDuration(value: $0) }
   }
 
   func remaining() -> RDuration {
-    RDuration(value: timer_Timer_remaining(ptr))
+    RDuration(value: driver_TimerDriver_remaining(ptr))
+  }
+
+  /// Saves the timer so that `init?(restoringFrom:)` can bring it back after the app restarts.
+  func save(forKey key: String) {
+    guard let json = withTimer({ timer_Timer_snapshotJson($0) }) else { return }
+    defer { rust_String_free(json) }
+    UserDefaults.standard.set(String(cString: json), forKey: key)
+  }
+
+  /// Calls `body` with a copy of the timer as it is now.
+  private func withTimer<Result>(_ body: (UnsafeMutableRawPointer) -> Result) -> Result {
+    let timer: UnsafeMutableRawPointer = driver_TimerDriver_timer(ptr)
+    defer { timer_Timer_free(timer) }
+    return body(timer)
+  }
+
+  /// Forwards the events of the driver to `onEvent`.
+  private func registerEventHandler() {
+    let context = Unmanaged.passUnretained(eventHandler).toOpaque()
+    driver_TimerDriver_onEvent(
+      ptr,
+      { event, context in
+        guard let context else { return }
+        Unmanaged<EventHandler>.fromOpaque(context).takeUnretainedValue().handle?(event)
+      }, context)
   }
 
   deinit {
-    timer_Timer_free(ptr)
+    // Freeing the driver joins its thread, so the event handler is not used afterwards.
+    driver_TimerDriver_free(ptr)
   }
 }
+
+/// The handler the driver of an `RTimer` calls for every event, kept alive by the timer.
+private final class EventHandler {
+  var handle: ((TimerEvent) -> Void)?
+}
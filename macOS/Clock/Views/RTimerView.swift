@@ -13,6 +13,7 @@ struct CountdownView: View {
   @Binding var timer: RTimer
   @Binding var isTimerRunning: Bool
   @Binding var isStarted: Bool
+  private let ticker = Timer.publish(every: 0.1, on: .main, in: .common).autoconnect()
 
   var progress: Double {
     ((timer.remaining().asSecs() / timer.duration.asSecs()))
@@ -39,20 +40,20 @@ struct CountdownView: View {
         Text(remainingTime).font(.system(size: 80)).fontWeight(.light).monospacedDigit()
       }
       .onAppear {
-        let queue = DispatchQueue(label: "updateStringQueue")
-        queue.async {
-          while true {
-            DispatchQueue.main.async {
-              updateView()
-            }
-            if timer.isDone {
-              timer.reset()
+        updateView()
+        timer.onEvent = { event in
+          DispatchQueue.main.async {
+            updateView()
+            if event.tag == TimerEvent_Expired {
               scheduleNotification()
             }
-            Thread.sleep(forTimeInterval: 0.03)
           }
         }
       }
+      .onDisappear { timer.onEvent = nil }
+      .onReceive(ticker) { _ in
+        updateView()
+      }
     }
   }
 
@@ -115,6 +116,7 @@ struct TimerPickerView: View {
     
     private func start() {
         self.timer = RTimer(secs: calculateDateAsSeconds())
+        self.timer.completionPolicy = CompletionPolicy(tag: AutoReset, .init())
         self.timer.start()
         self.timerStarted = !self.timer.hasNotStarted
     }
@@ -128,7 +130,10 @@ struct TimerPickerView: View {
 }
 
 struct RTimerView: View {
-  @State private var rTimer: RTimer = RTimer(secs: 0)
+  private static let storageKey = "RTimerView.timer"
+
+  @State private var rTimer: RTimer =
+    RTimer(restoringFrom: RTimerView.storageKey) ?? RTimer(secs: 0)
   @State private var secs = 0
   @State private var isTimerRunning = false
   @State private var timerStarted = false
@@ -157,6 +162,13 @@ struct RTimerView: View {
           TimerPickerView(timer: $rTimer, timerStarted: $timerStarted)
       }
     }.animation(.default, value: timerStarted)
+      .onAppear { timerStarted = !rTimer.hasNotStarted }
+      .onDisappear { rTimer.save(forKey: RTimerView.storageKey) }
+      .onReceive(
+        NotificationCenter.default.publisher(for: NSApplication.willTerminateNotification)
+      ) { _ in
+        rTimer.save(forKey: RTimerView.storageKey)
+      }
   }
 }
 
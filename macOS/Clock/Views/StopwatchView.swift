@@ -8,7 +8,10 @@
 import SwiftUI
 
 struct StopwatchView: View {
-  @State private var stopwatch: Stopwatch = Stopwatch()
+  private static let storageKey = "StopwatchView.stopwatch"
+
+  @State private var stopwatch: Stopwatch =
+    Stopwatch(restoringFrom: StopwatchView.storageKey) ?? Stopwatch()
   @State private var elapsedTime = "00:00.00"
   @SceneStorage("StopwatchView.isRunning") private var isRunning: Bool = false
   @State private var timer = Timer.publish(every: 1, on: .main, in: .common).autoconnect()
@@ -28,7 +31,7 @@ struct StopwatchView: View {
         }
       }
       
-      LapTimeTable(laptimes: stopwatch.lapTimes)
+      LapTimeTable(stopwatch: stopwatch)
       
       HStack {
         if isRunning {
@@ -53,6 +56,12 @@ struct StopwatchView: View {
       }
     }
     .padding()
+    .onDisappear { stopwatch.save(forKey: StopwatchView.storageKey) }
+    .onReceive(
+      NotificationCenter.default.publisher(for: NSApplication.willTerminateNotification)
+    ) { _ in
+      stopwatch.save(forKey: StopwatchView.storageKey)
+    }
   }
 }
 
@@ -1,6 +1,6 @@
 #!/bin/sh
 
-cargo build --release --target aarch64-apple-darwin
+cargo build --release --target aarch64-apple-darwin --features json
 cbindgen --config cbindgen.toml --crate clock --output include/clock.h
 
 cp target/aarch64-apple-darwin/release/libclock.a macOS/Clock/Frameworks/libclock.a
\ No newline at end of file